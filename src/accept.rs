@@ -0,0 +1,118 @@
+/// Member of a header list weighted by a quality value, such as
+/// `gzip;q=0.8` or `text/html;level=1;q=0.5`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Item<'a> {
+    quality: u16,
+    value: &'a str,
+}
+
+impl<'a> Item<'a> {
+    // Quality of the item in thousandths, from 0 to 1000.
+    pub const fn quality(&self) -> u16 {
+        self.quality
+    }
+
+    // Retrieve the value of the item without its parameters.
+    pub const fn value(&self) -> &'a str {
+        self.value
+    }
+}
+
+/// Parse the items of a header such as `Accept` or `Accept-Encoding`.
+///
+/// Items with invalid quality values are skipped.
+pub fn parse(header: &[u8]) -> Vec<Item<'_>> {
+    let header = match std::str::from_utf8(header) {
+        Ok(header) => header,
+        Err(_) => return Vec::new(),
+    };
+
+    header
+        .split(',')
+        .filter_map(|item| {
+            let mut params = item.split(';').map(str::trim);
+            let value = params.next().filter(|value| !value.is_empty())?;
+            let quality = params
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .map_or(Some(1000), |(_, quality)| parse_quality(quality.trim()))?;
+
+            Some(Item { quality, value })
+        })
+        .collect()
+}
+
+/// Whether a header such as `Accept` explicitly lists a value as acceptable,
+/// without relying on wildcards.
+pub fn includes(header: &[u8], value: &str) -> bool {
+    parse(header)
+        .iter()
+        .any(|item| item.quality > 0 && item.value.eq_ignore_ascii_case(value))
+}
+
+/// Parse a quality value such as `1`, `0.5`, or `0.125` into thousandths.
+fn parse_quality(input: &str) -> Option<u16> {
+    let (whole, fraction) = input.split_once('.').unwrap_or((input, ""));
+
+    if fraction.len() > 3 || !fraction.bytes().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+
+    let fraction = fraction
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |value, digit| value * 10 + u16::from(digit - b'0'));
+
+    match whole {
+        "0" => Some(fraction),
+        "1" if fraction == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{includes, parse, parse_quality, Item};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            vec![
+                Item {
+                    quality: 1000,
+                    value: "text/html"
+                },
+                Item {
+                    quality: 500,
+                    value: "text/plain"
+                },
+                Item {
+                    quality: 0,
+                    value: "*/*"
+                },
+            ],
+            parse(b"text/html;level=1, text/plain ; Q=0.5,, */*;q=0, a;q=2")
+        );
+    }
+
+    #[test]
+    fn test_includes() {
+        let accept = b"text/html,application/xhtml+xml,*/*;q=0.8";
+        assert!(includes(accept, "text/html"));
+        assert!(!includes(accept, "image/png"));
+        assert!(!includes(b"text/html;q=0", "text/html"));
+    }
+
+    #[test]
+    fn test_parse_quality() {
+        assert_eq!(Some(1000), parse_quality("1"));
+        assert_eq!(Some(1000), parse_quality("1.000"));
+        assert_eq!(Some(500), parse_quality("0.5"));
+        assert_eq!(Some(125), parse_quality("0.125"));
+        assert_eq!(Some(0), parse_quality("0"));
+        assert_eq!(None, parse_quality("1.5"));
+        assert_eq!(None, parse_quality("0.1234"));
+        assert_eq!(None, parse_quality("x"));
+    }
+}
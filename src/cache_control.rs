@@ -0,0 +1,105 @@
+use super::glob::Glob;
+
+/// Policy for files whose names contain a content hash, which never change.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// `Cache-Control` value applied to paths matching a pattern.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pattern: Glob,
+    value: String,
+}
+
+impl Rule {
+    /// Parse a rule in the form `PATTERN=VALUE`, such as
+    /// `*.html=no-cache`.
+    pub fn new(rule: &str) -> Option<Self> {
+        let (pattern, value) = rule.split_once('=')?;
+        let value = value.trim();
+
+        if pattern.is_empty()
+            || value.is_empty()
+            || !value.bytes().all(|x| x == b' ' || x.is_ascii_graphic())
+        {
+            return None;
+        }
+
+        Some(Self {
+            pattern: Glob::new(pattern),
+            value: value.to_owned(),
+        })
+    }
+}
+
+/// Resolve the `Cache-Control` value of a request path relative to the served
+/// directory, which may differ from the path of the file served for it.
+///
+/// The first matching rule is used. Without one, fingerprinted files are
+/// cached for as long as possible.
+pub fn resolve<'a>(rules: &'a [Rule], path: &str) -> Option<&'a str> {
+    match rules.iter().find(|rule| rule.pattern.matches(path)) {
+        Some(rule) => Some(&rule.value),
+        None if is_fingerprinted(path) => Some(IMMUTABLE),
+        None => None,
+    }
+}
+
+/// Whether a file name contains a content hash, such as `app.3f9a1c.js` or
+/// `app-3f9a1c2b.js`.
+///
+/// A hash is a segment of the name other than the first and the extension of
+/// at least six hexadecimal characters, including both letters and digits.
+fn is_fingerprinted(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = match name.rsplit_once('.') {
+        Some((stem, _)) => stem,
+        None => return false,
+    };
+
+    stem.split(['.', '-']).skip(1).any(|segment| {
+        segment.len() >= 6
+            && segment.bytes().all(|x| x.is_ascii_hexdigit())
+            && segment.bytes().any(|x| x.is_ascii_digit())
+            && segment.bytes().any(|x| x.is_ascii_alphabetic())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_fingerprinted, resolve, Rule, IMMUTABLE};
+
+    #[test]
+    fn test_rule() {
+        assert!(Rule::new("*.html=no-cache").is_some());
+        assert!(Rule::new("no-cache").is_none());
+        assert!(Rule::new("=no-cache").is_none());
+        assert!(Rule::new("*.html=").is_none());
+        assert!(Rule::new("*.html=no-cache\r\nX: y").is_none());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let rules = [
+            Rule::new("*.html=no-cache").unwrap(),
+            Rule::new("assets/**=public, max-age=31536000, immutable").unwrap(),
+            Rule::new("**=public, max-age=60").unwrap(),
+        ];
+
+        assert_eq!(Some("no-cache"), resolve(&rules, "docs/index.html"));
+        assert_eq!(Some(IMMUTABLE), resolve(&rules, "assets/app.js"));
+        assert_eq!(Some("public, max-age=60"), resolve(&rules, "app.3f9a1c.js"));
+        assert_eq!(Some(IMMUTABLE), resolve(&[], "js/app.3f9a1c.js"));
+        assert_eq!(None, resolve(&[], "app.js"));
+    }
+
+    #[test]
+    fn test_is_fingerprinted() {
+        assert!(is_fingerprinted("app.3f9a1c.js"));
+        assert!(is_fingerprinted("static/main-3f9a1c2b.min.css"));
+        assert!(!is_fingerprinted("3f9a1c.js"));
+        assert!(!is_fingerprinted("app.js"));
+        assert!(!is_fingerprinted("report-20240101.pdf"));
+        assert!(!is_fingerprinted("app.facade.js"));
+        assert!(!is_fingerprinted("app.3f9a1c"));
+    }
+}
@@ -0,0 +1,201 @@
+use super::content_type::Mime;
+use std::str;
+
+/// Number of bytes of an HTML page scanned for a `<meta charset>` element.
+const META_SCAN_LEN: usize = 1024;
+
+/// Character encoding of text content.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Charset {
+    Big5,
+    EucJp,
+    EucKr,
+    Gbk,
+    Iso8859_1,
+    ShiftJis,
+    Utf16Be,
+    Utf16Le,
+    Utf8,
+    Windows1252,
+}
+
+impl Charset {
+    /// Parse a charset from one of its case-insensitive labels, such as
+    /// `utf-8` or `latin1`.
+    pub fn new(label: &str) -> Option<Self> {
+        Some(match label.trim().to_ascii_lowercase().as_str() {
+            "big5" => Self::Big5,
+            "euc-jp" => Self::EucJp,
+            "euc-kr" => Self::EucKr,
+            "gbk" | "gb2312" => Self::Gbk,
+            "iso-8859-1" | "latin1" => Self::Iso8859_1,
+            "shift_jis" | "sjis" => Self::ShiftJis,
+            "utf-16be" => Self::Utf16Be,
+            "utf-16le" => Self::Utf16Le,
+            "utf-8" | "utf8" => Self::Utf8,
+            "windows-1252" | "cp1252" => Self::Windows1252,
+            _ => return None,
+        })
+    }
+
+    /// Detect the charset of text content, which is only a prefix of a file
+    /// if `partial`.
+    ///
+    /// A byte order mark takes precedence, followed by the `<meta charset>`
+    /// of HTML pages, then whether the content is valid UTF-8. Content of
+    /// other types, or in none of these, is given the default.
+    pub fn detect(
+        mime: Mime,
+        content: &[u8],
+        partial: bool,
+        default: Option<Self>,
+    ) -> Option<Self> {
+        if !mime.is_text() {
+            return None;
+        }
+
+        if let Some(charset) = Self::from_bom(content) {
+            return Some(charset);
+        }
+
+        if let Some(charset) = Some(mime)
+            .filter(|mime| *mime == Mime::TextHtml)
+            .and_then(|_| meta(content))
+        {
+            return Some(charset);
+        }
+
+        match str::from_utf8(content) {
+            // A prefix may end partway through a character, but whole
+            // content may not.
+            Err(error) if error.error_len().is_some() || !partial => default,
+            _ => Some(Self::Utf8),
+        }
+    }
+
+    /// Charset indicated by the byte order mark at the start of content.
+    pub fn from_bom(content: &[u8]) -> Option<Self> {
+        match content {
+            [0xef, 0xbb, 0xbf, ..] => Some(Self::Utf8),
+            [0xfe, 0xff, ..] => Some(Self::Utf16Be),
+            [0xff, 0xfe, ..] => Some(Self::Utf16Le),
+            _ => None,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Big5 => "big5",
+            Self::EucJp => "euc-jp",
+            Self::EucKr => "euc-kr",
+            Self::Gbk => "gbk",
+            Self::Iso8859_1 => "iso-8859-1",
+            Self::ShiftJis => "shift_jis",
+            Self::Utf16Be => "utf-16be",
+            Self::Utf16Le => "utf-16le",
+            Self::Utf8 => "utf-8",
+            Self::Windows1252 => "windows-1252",
+        }
+    }
+}
+
+/// Find the charset declared by a `<meta charset>` element or a
+/// `<meta http-equiv="Content-Type">` element at the start of an HTML page.
+fn meta(content: &[u8]) -> Option<Charset> {
+    let head = &content[..content.len().min(META_SCAN_LEN)];
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    head.split("<meta").skip(1).find_map(|element| {
+        let element = element.split('>').next().unwrap_or_default();
+        let start = element.find("charset=")? + "charset=".len();
+        let label = element[start..]
+            .trim_start_matches(['"', '\''])
+            .split(|c: char| c == '"' || c == '\'' || c == ';' || c == '/' || c.is_whitespace())
+            .next()?;
+
+        Charset::new(label)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Charset;
+    use crate::content_type::Mime;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Some(Charset::Utf8), Charset::new("UTF-8"));
+        assert_eq!(Some(Charset::Iso8859_1), Charset::new("latin1"));
+        assert!(Charset::new("utf-7").is_none());
+    }
+
+    #[test]
+    fn test_detect() {
+        let default = Some(Charset::Windows1252);
+
+        assert_eq!(
+            Some(Charset::Utf8),
+            Charset::detect(
+                Mime::TextCss,
+                "a::after { content: '→' }".as_bytes(),
+                false,
+                None
+            )
+        );
+        assert_eq!(
+            Some(Charset::Utf16Le),
+            Charset::detect(Mime::TextPlain, b"\xff\xfea\x00", false, default)
+        );
+        assert_eq!(
+            Some(Charset::Utf8),
+            Charset::detect(Mime::TextPlain, b"\xef\xbb\xbfa", false, default)
+        );
+        assert_eq!(
+            default,
+            Charset::detect(Mime::TextPlain, b"caf\xe9 au lait", false, default)
+        );
+        assert_eq!(
+            None,
+            Charset::detect(Mime::TextPlain, b"caf\xe9 au lait", false, None)
+        );
+        // A prefix may cut a character short, but whole content may not.
+        assert_eq!(
+            Some(Charset::Utf8),
+            Charset::detect(Mime::TextPlain, b"caf\xc3", true, None)
+        );
+        assert_eq!(
+            default,
+            Charset::detect(Mime::TextPlain, b"caf\xc3", false, default)
+        );
+        assert_eq!(
+            None,
+            Charset::detect(Mime::ImagePng, b"png", false, default)
+        );
+    }
+
+    #[test]
+    fn test_meta() {
+        assert_eq!(
+            Some(Charset::ShiftJis),
+            Charset::detect(
+                Mime::TextHtml,
+                b"<html><head><meta charset=\"Shift_JIS\">",
+                false,
+                None
+            )
+        );
+        assert_eq!(
+            Some(Charset::Iso8859_1),
+            Charset::detect(
+                Mime::TextHtml,
+                b"<META http-equiv='Content-Type' content='text/html; charset=ISO-8859-1'>",
+                false,
+                None
+            )
+        );
+        assert_eq!(
+            Some(Charset::Utf8),
+            Charset::detect(Mime::TextHtml, b"<meta name=viewport><p>", false, None)
+        );
+    }
+}
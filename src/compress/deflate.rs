@@ -0,0 +1,652 @@
+//! DEFLATE compression as specified by RFC 1951.
+//!
+//! Input is tokenized with a greedy LZ77 matcher over hash chains and each
+//! block is written with whichever of dynamic Huffman codes, fixed Huffman
+//! codes, or no compression is smallest.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Distance back that matches may refer to.
+const WINDOW_SIZE: usize = 32_768;
+
+const MIN_MATCH: usize = 3;
+
+const MAX_MATCH: usize = 258;
+
+/// Number of candidates to compare before settling for the best match so far.
+const MAX_CHAIN: usize = 64;
+
+const HASH_SIZE: usize = 1 << 15;
+
+/// Number of tokens after which a new block with fresh codes is started.
+const BLOCK_TOKENS: usize = 16_384;
+
+/// Largest amount of data in a stored block.
+const MAX_STORED: usize = 65_535;
+
+const END_OF_BLOCK: usize = 256;
+
+/// Number of literal/length and distance symbols that may be used.
+const LITERAL_CODES: usize = 286;
+const DISTANCE_CODES: usize = 30;
+
+const LENGTH_BASE: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+
+const LENGTH_EXTRA: [usize; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+const DISTANCE_BASE: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+const DISTANCE_EXTRA: [usize; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order in which code length code lengths are written.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+#[derive(Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match { distance: usize, length: usize },
+}
+
+/// Compress data into a raw DEFLATE stream.
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+
+    if input.is_empty() {
+        // A final block with fixed codes containing only the end of block.
+        writer.write(0b011, 3);
+        writer.write(0, 7);
+
+        return writer.finish();
+    }
+
+    let mut matcher = Matcher::new();
+    let mut tokens = Vec::with_capacity(BLOCK_TOKENS);
+    let mut position = 0;
+
+    while position < input.len() {
+        let start = position;
+        tokens.clear();
+
+        while position < input.len() && tokens.len() < BLOCK_TOKENS {
+            if let Some((length, distance)) = matcher.find(input, position) {
+                tokens.push(Token::Match { distance, length });
+
+                for idx in position..position + length {
+                    matcher.insert(input, idx);
+                }
+
+                position += length;
+            } else {
+                tokens.push(Token::Literal(input[position]));
+                matcher.insert(input, position);
+                position += 1;
+            }
+        }
+
+        write_block(
+            &mut writer,
+            &tokens,
+            &input[start..position],
+            position == input.len(),
+        );
+    }
+
+    writer.finish()
+}
+
+/// Finds previous occurrences of data via chains of positions with the same
+/// hash of their first bytes.
+struct Matcher {
+    head: Vec<usize>,
+    previous: Vec<usize>,
+}
+
+impl Matcher {
+    const NONE: usize = usize::MAX;
+
+    fn new() -> Self {
+        Self {
+            head: vec![Self::NONE; HASH_SIZE],
+            previous: vec![Self::NONE; WINDOW_SIZE],
+        }
+    }
+
+    fn hash(input: &[u8], position: usize) -> usize {
+        let bytes = &input[position..position + MIN_MATCH];
+
+        ((usize::from(bytes[0]) << 10) ^ (usize::from(bytes[1]) << 5) ^ usize::from(bytes[2]))
+            % HASH_SIZE
+    }
+
+    fn insert(&mut self, input: &[u8], position: usize) {
+        if position + MIN_MATCH > input.len() {
+            return;
+        }
+
+        let hash = Self::hash(input, position);
+        self.previous[position % WINDOW_SIZE] = self.head[hash];
+        self.head[hash] = position;
+    }
+
+    /// Find the longest match for the data at a position, returning its length
+    /// and distance.
+    fn find(&self, input: &[u8], position: usize) -> Option<(usize, usize)> {
+        if position + MIN_MATCH > input.len() {
+            return None;
+        }
+
+        let max_length = MAX_MATCH.min(input.len() - position);
+        let mut candidate = self.head[Self::hash(input, position)];
+        let mut best: Option<(usize, usize)> = None;
+
+        for _ in 0..MAX_CHAIN {
+            if candidate == Self::NONE || position - candidate > WINDOW_SIZE {
+                break;
+            }
+
+            let length = input[candidate..]
+                .iter()
+                .zip(&input[position..position + max_length])
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            if length >= MIN_MATCH && best.map_or(true, |(best, _)| length > best) {
+                best = Some((length, position - candidate));
+
+                if length == max_length {
+                    break;
+                }
+            }
+
+            let next = self.previous[candidate % WINDOW_SIZE];
+
+            if next == Self::NONE || next >= candidate {
+                break;
+            }
+
+            candidate = next;
+        }
+
+        best
+    }
+}
+
+fn write_block(writer: &mut BitWriter, tokens: &[Token], raw: &[u8], last: bool) {
+    let mut literal_frequencies = [0; LITERAL_CODES];
+    let mut distance_frequencies = [0; DISTANCE_CODES];
+    literal_frequencies[END_OF_BLOCK] = 1;
+
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => literal_frequencies[usize::from(byte)] += 1,
+            Token::Match { distance, length } => {
+                literal_frequencies[257 + length_index(length)] += 1;
+                distance_frequencies[distance_index(distance)] += 1;
+            }
+        }
+    }
+
+    let literal_lengths = huffman_lengths(&literal_frequencies, 15);
+    let distance_lengths = huffman_lengths(&distance_frequencies, 15);
+    let header = DynamicHeader::new(&literal_lengths, &distance_lengths);
+    let (fixed_literal_lengths, fixed_distance_lengths) = fixed_lengths();
+
+    let dynamic_cost = header.cost()
+        + data_cost(
+            &literal_frequencies,
+            &distance_frequencies,
+            &literal_lengths,
+            &distance_lengths,
+        );
+    let fixed_cost = data_cost(
+        &literal_frequencies,
+        &distance_frequencies,
+        &fixed_literal_lengths,
+        &fixed_distance_lengths,
+    );
+    let stored_cost = (raw.len() / MAX_STORED + 1) * 40 + raw.len() * 8;
+
+    if stored_cost <= dynamic_cost.min(fixed_cost) {
+        let chunks = raw.chunks(MAX_STORED).count();
+
+        for (idx, chunk) in raw.chunks(MAX_STORED).enumerate() {
+            writer.write(usize::from(last && idx + 1 == chunks), 3);
+            writer.align();
+            writer.write_u16(chunk.len());
+            writer.write_u16(!chunk.len() & 0xffff);
+            writer.write_bytes(chunk);
+        }
+    } else if fixed_cost <= dynamic_cost {
+        writer.write(0b010 | usize::from(last), 3);
+        write_tokens(
+            writer,
+            tokens,
+            &fixed_literal_lengths,
+            &fixed_distance_lengths,
+        );
+    } else {
+        writer.write(0b100 | usize::from(last), 3);
+        header.write(writer);
+        write_tokens(writer, tokens, &literal_lengths, &distance_lengths);
+    }
+}
+
+fn write_tokens(
+    writer: &mut BitWriter,
+    tokens: &[Token],
+    literal_lengths: &[usize],
+    distance_lengths: &[usize],
+) {
+    let literal_codes = codes(literal_lengths);
+    let distance_codes = codes(distance_lengths);
+
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => {
+                let symbol = usize::from(byte);
+                writer.write(literal_codes[symbol], literal_lengths[symbol]);
+            }
+            Token::Match { distance, length } => {
+                let idx = length_index(length);
+                writer.write(literal_codes[257 + idx], literal_lengths[257 + idx]);
+                writer.write(length - LENGTH_BASE[idx], LENGTH_EXTRA[idx]);
+
+                let idx = distance_index(distance);
+                writer.write(distance_codes[idx], distance_lengths[idx]);
+                writer.write(distance - DISTANCE_BASE[idx], DISTANCE_EXTRA[idx]);
+            }
+        }
+    }
+
+    writer.write(literal_codes[END_OF_BLOCK], literal_lengths[END_OF_BLOCK]);
+}
+
+/// Number of bits used to write the symbols of a block with their extra bits.
+fn data_cost(
+    literal_frequencies: &[usize],
+    distance_frequencies: &[usize],
+    literal_lengths: &[usize],
+    distance_lengths: &[usize],
+) -> usize {
+    let literals = literal_frequencies
+        .iter()
+        .enumerate()
+        .map(|(symbol, frequency)| {
+            let extra = symbol.checked_sub(257).map_or(0, |idx| LENGTH_EXTRA[idx]);
+
+            frequency * (literal_lengths[symbol] + extra)
+        })
+        .sum::<usize>();
+    let distances = distance_frequencies
+        .iter()
+        .enumerate()
+        .map(|(symbol, frequency)| frequency * (distance_lengths[symbol] + DISTANCE_EXTRA[symbol]))
+        .sum::<usize>();
+
+    literals + distances
+}
+
+/// Code lengths of the fixed Huffman codes for literals/lengths and distances.
+fn fixed_lengths() -> (Vec<usize>, Vec<usize>) {
+    let literals = (0..288)
+        .map(|symbol| match symbol {
+            0..=143 | 280..=287 => 8,
+            144..=255 => 9,
+            _ => 7,
+        })
+        .collect();
+
+    (literals, vec![5; 32])
+}
+
+fn length_index(length: usize) -> usize {
+    LENGTH_BASE
+        .iter()
+        .rposition(|base| *base <= length)
+        .unwrap_or(0)
+}
+
+fn distance_index(distance: usize) -> usize {
+    DISTANCE_BASE
+        .iter()
+        .rposition(|base| *base <= distance)
+        .unwrap_or(0)
+}
+
+/// Compute length-limited Huffman code lengths for symbol frequencies.
+///
+/// At least two symbols are always given codes, since decoders may reject
+/// incomplete codes.
+fn huffman_lengths(frequencies: &[usize], max_bits: usize) -> Vec<usize> {
+    let mut lengths = vec![0; frequencies.len()];
+    let mut symbols = (0..frequencies.len())
+        .filter(|symbol| frequencies[*symbol] > 0)
+        .collect::<Vec<_>>();
+
+    if symbols.len() < 2 {
+        let used = symbols.first().copied().unwrap_or(0);
+        lengths[used] = 1;
+        lengths[usize::from(used == 0)] = 1;
+
+        return lengths;
+    }
+
+    // Build the tree, where leaves are indices into `symbols` and internal
+    // nodes follow them.
+    let mut parents = vec![0; symbols.len() * 2 - 1];
+    let mut heap = symbols
+        .iter()
+        .enumerate()
+        .map(|(node, symbol)| Reverse((frequencies[*symbol], node)))
+        .collect::<BinaryHeap<_>>();
+    let mut next = symbols.len();
+
+    while let (Some(Reverse((a, left))), Some(Reverse((b, right)))) = (heap.pop(), heap.pop()) {
+        parents[left] = next;
+        parents[right] = next;
+        heap.push(Reverse((a + b, next)));
+        next += 1;
+    }
+
+    // Parents are created after their children, so depths can be resolved
+    // from the root downwards.
+    let mut depths = vec![0; parents.len()];
+
+    for node in (0..parents.len() - 1).rev() {
+        depths[node] = depths[parents[node]] + 1;
+    }
+
+    let mut counts = vec![0_usize; max_bits + 1];
+
+    for depth in &depths[..symbols.len()] {
+        counts[(*depth).min(max_bits)] += 1;
+    }
+
+    // Moving codes that were too long to the limit oversubscribes the code,
+    // so lengthen shorter codes until it's complete again.
+    let mut total = (1..=max_bits)
+        .map(|bits| counts[bits] << (max_bits - bits))
+        .sum::<usize>();
+
+    while total > 1 << max_bits {
+        counts[max_bits] -= 1;
+
+        if let Some(bits) = (1..max_bits).rev().find(|bits| counts[*bits] > 0) {
+            counts[bits] -= 1;
+            counts[bits + 1] += 2;
+        }
+
+        total -= 1;
+    }
+
+    // Give the shortest codes to the most frequent symbols.
+    symbols.sort_by_key(|symbol| Reverse(frequencies[*symbol]));
+    let mut symbols = symbols.into_iter();
+
+    for (bits, count) in counts.iter().enumerate().skip(1) {
+        for symbol in symbols.by_ref().take(*count) {
+            lengths[symbol] = bits;
+        }
+    }
+
+    lengths
+}
+
+/// Assign canonical codes to code lengths, bit-reversed for writing.
+fn codes(lengths: &[usize]) -> Vec<usize> {
+    let max_bits = lengths.iter().copied().max().unwrap_or(0);
+    let mut counts = vec![0; max_bits + 1];
+
+    for length in lengths.iter().filter(|length| **length > 0) {
+        counts[*length] += 1;
+    }
+
+    let mut next_code = vec![0; max_bits + 1];
+    let mut code = 0;
+
+    for bits in 1..=max_bits {
+        code = (code + counts[bits - 1]) << 1;
+        next_code[bits] = code;
+    }
+
+    lengths
+        .iter()
+        .map(|length| {
+            if *length == 0 {
+                return 0;
+            }
+
+            let code = next_code[*length];
+            next_code[*length] += 1;
+
+            (0..*length).fold(0, |reversed, bit| (reversed << 1) | ((code >> bit) & 1))
+        })
+        .collect()
+}
+
+/// Code lengths of a dynamic block, themselves compressed with run-length
+/// encoding and a Huffman code.
+struct DynamicHeader {
+    code_length_lengths: Vec<usize>,
+    code_length_count: usize,
+    distance_count: usize,
+    literal_count: usize,
+    /// Symbols of the code lengths with their extra bits and number of extra
+    /// bits.
+    symbols: Vec<(usize, usize, usize)>,
+}
+
+impl DynamicHeader {
+    fn new(literal_lengths: &[usize], distance_lengths: &[usize]) -> Self {
+        let count = |lengths: &[usize], min| {
+            lengths
+                .iter()
+                .rposition(|length| *length > 0)
+                .map_or(min, |idx| (idx + 1).max(min))
+        };
+        let literal_count = count(literal_lengths, 257);
+        let distance_count = count(distance_lengths, 1);
+
+        let lengths = literal_lengths[..literal_count]
+            .iter()
+            .chain(&distance_lengths[..distance_count])
+            .copied()
+            .collect::<Vec<_>>();
+        let mut symbols = Vec::new();
+        let mut idx = 0;
+
+        while idx < lengths.len() {
+            let length = lengths[idx];
+            let run = lengths[idx..]
+                .iter()
+                .take_while(|other| **other == length)
+                .count();
+            let mut remaining = run;
+
+            if length == 0 {
+                while remaining >= 11 {
+                    let repeat = remaining.min(138);
+                    symbols.push((18, repeat - 11, 7));
+                    remaining -= repeat;
+                }
+
+                if remaining >= 3 {
+                    symbols.push((17, remaining - 3, 3));
+                    remaining = 0;
+                }
+            } else {
+                symbols.push((length, 0, 0));
+                remaining -= 1;
+
+                while remaining >= 3 {
+                    let repeat = remaining.min(6);
+                    symbols.push((16, repeat - 3, 2));
+                    remaining -= repeat;
+                }
+            }
+
+            symbols.extend((0..remaining).map(|_| (length, 0, 0)));
+            idx += run;
+        }
+
+        let mut frequencies = [0; 19];
+
+        for (symbol, _, _) in &symbols {
+            frequencies[*symbol] += 1;
+        }
+
+        let code_length_lengths = huffman_lengths(&frequencies, 7);
+        let code_length_count = CODE_LENGTH_ORDER
+            .iter()
+            .rposition(|symbol| code_length_lengths[*symbol] > 0)
+            .map_or(4, |idx| (idx + 1).max(4));
+
+        Self {
+            code_length_lengths,
+            code_length_count,
+            distance_count,
+            literal_count,
+            symbols,
+        }
+    }
+
+    fn cost(&self) -> usize {
+        14 + self.code_length_count * 3
+            + self
+                .symbols
+                .iter()
+                .map(|(symbol, _, extra_bits)| self.code_length_lengths[*symbol] + extra_bits)
+                .sum::<usize>()
+    }
+
+    fn write(&self, writer: &mut BitWriter) {
+        writer.write(self.literal_count - 257, 5);
+        writer.write(self.distance_count - 1, 5);
+        writer.write(self.code_length_count - 4, 4);
+
+        for symbol in &CODE_LENGTH_ORDER[..self.code_length_count] {
+            writer.write(self.code_length_lengths[*symbol], 3);
+        }
+
+        let codes = codes(&self.code_length_lengths);
+
+        for (symbol, extra, extra_bits) in &self.symbols {
+            writer.write(codes[*symbol], self.code_length_lengths[*symbol]);
+            writer.write(*extra, *extra_bits);
+        }
+    }
+}
+
+/// Writer of values packed starting from the least significant bit.
+#[derive(Default)]
+struct BitWriter {
+    bits: u64,
+    count: usize,
+    output: Vec<u8>,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: usize, count: usize) {
+        self.bits |= (value as u64) << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.output.push(self.bits.to_le_bytes()[0]);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Pad with zero bits up to the next byte boundary.
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.write(0, 8 - self.count);
+        }
+    }
+
+    fn write_u16(&mut self, value: usize) {
+        self.write(value, 16);
+    }
+
+    /// Write bytes, which must be preceded by aligning.
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.output.extend_from_slice(bytes);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.align();
+
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{codes, compress, huffman_lengths};
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(vec![0x03, 0x00], compress(b""));
+    }
+
+    #[test]
+    fn test_stored() {
+        // Data without repetition is cheapest to store uncompressed.
+        let input = (0..=255).collect::<Vec<u8>>();
+        let mut expected = vec![0x01, 0x00, 0x01, 0xff, 0xfe];
+        expected.extend(&input);
+
+        assert_eq!(expected, compress(&input));
+    }
+
+    #[test]
+    fn test_fixed() {
+        // Verified with Python's `zlib.decompress(data, -15)`.
+        assert_eq!(vec![0x4b, 0x04, 0x00], compress(b"a"));
+        assert_eq!(vec![0x4b, 0x24, 0x19, 0x00, 0x00], compress(&[b'a'; 50]));
+    }
+
+    #[test]
+    fn test_huffman_lengths() {
+        assert_eq!(vec![1, 1, 0], huffman_lengths(&[0, 5, 0], 15));
+        assert_eq!(vec![1, 2, 3, 3], huffman_lengths(&[8, 4, 2, 1], 15));
+
+        // Frequencies of a Fibonacci sequence produce a maximally deep tree.
+        let frequencies = (0..20)
+            .scan((1, 1), |state, _| {
+                *state = (state.1, state.0 + state.1);
+
+                Some(state.0)
+            })
+            .collect::<Vec<_>>();
+        let lengths = huffman_lengths(&frequencies, 7);
+        let kraft = lengths
+            .iter()
+            .map(|length| 1 << (7 - length))
+            .sum::<usize>();
+        assert!(lengths.iter().all(|length| (1..=7).contains(length)));
+        assert_eq!(1 << 7, kraft);
+    }
+
+    #[test]
+    fn test_codes() {
+        // Example from RFC 1951 section 3.2.2, bit-reversed.
+        assert_eq!(
+            vec![0b010, 0b110, 0b001, 0b101, 0b011, 0b00, 0b0111, 0b1111],
+            codes(&[3, 3, 3, 3, 3, 2, 4, 4])
+        );
+    }
+}
@@ -0,0 +1,199 @@
+mod deflate;
+
+use super::accept::{self, Item};
+
+/// Table for computing CRC-32 checksums with the reversed polynomial
+/// `0xEDB88320`.
+const CRC_TABLE: [u32; 256] = crc_table();
+
+/// Content coding of a response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    Brotli,
+    Deflate,
+    Gzip,
+    Zstd,
+}
+
+impl Encoding {
+    /// Encodings that may be applied on the fly, in order of preference.
+    pub const COMPRESSIBLE: &'static [Self] = &[Self::Gzip, Self::Deflate];
+
+    /// Encodings that may be served from precompressed sidecar files, in
+    /// order of preference.
+    pub const PRECOMPRESSED: &'static [Self] = &[Self::Brotli, Self::Zstd, Self::Gzip];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Deflate => "deflate",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Extension appended to the name of a file to name its precompressed
+    /// sidecar, such as `br` for `app.js.br`.
+    pub const fn sidecar_extension(self) -> Option<&'static str> {
+        match self {
+            Self::Brotli => Some("br"),
+            Self::Deflate => None,
+            Self::Gzip => Some("gz"),
+            Self::Zstd => Some("zst"),
+        }
+    }
+
+    /// Choose the available encoding that's most acceptable according to the
+    /// value of an `Accept-Encoding` header.
+    ///
+    /// Encodings not listed are acceptable if a `*` is. Ties are broken by
+    /// the order of the available encodings.
+    pub fn negotiate(accept_encoding: &[u8], available: &[Self]) -> Option<Self> {
+        let items = accept::parse(accept_encoding);
+        let quality = |name: &str| {
+            items
+                .iter()
+                .find(|item| item.value().eq_ignore_ascii_case(name))
+                .or_else(|| items.iter().find(|item| item.value() == "*"))
+                .map_or(0, Item::quality)
+        };
+
+        available
+            .iter()
+            .map(|encoding| (*encoding, quality(encoding.name())))
+            .filter(|(_, quality)| *quality > 0)
+            .fold(
+                None,
+                |best: Option<(Self, u16)>, (encoding, quality)| match best {
+                    Some((_, best_quality)) if best_quality >= quality => best,
+                    _ => Some((encoding, quality)),
+                },
+            )
+            .map(|(encoding, _)| encoding)
+    }
+
+    /// Compress data with the encoding, if it can be applied on the fly.
+    pub fn encode(self, input: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Self::Brotli | Self::Zstd => None,
+            Self::Deflate => Some(zlib(input)),
+            Self::Gzip => Some(gzip(input)),
+        }
+    }
+}
+
+/// Wrap DEFLATE compressed data in the gzip format of RFC 1952.
+fn gzip(input: &[u8]) -> Vec<u8> {
+    // Magic, DEFLATE method, no flags or modification time, and an unknown
+    // operating system.
+    let mut output = vec![0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff];
+    output.extend(deflate::compress(input));
+    output.extend(crc32(input).to_le_bytes());
+    // The size is stored modulo 2^32.
+    output.extend(&input.len().to_le_bytes()[..4]);
+
+    output
+}
+
+/// Wrap DEFLATE compressed data in the zlib format of RFC 1950, which is what
+/// the `deflate` content coding means.
+fn zlib(input: &[u8]) -> Vec<u8> {
+    // A 32 KiB window with the default compression level.
+    let mut output = vec![0x78, 0x9c];
+    output.extend(deflate::compress(input));
+    output.extend(adler32(input).to_be_bytes());
+
+    output
+}
+
+// Indices are below 256.
+#[allow(clippy::cast_possible_truncation)]
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut idx = 0;
+
+    while idx < table.len() {
+        let mut crc = idx as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[idx] = crc;
+        idx += 1;
+    }
+
+    table
+}
+
+fn crc32(input: &[u8]) -> u32 {
+    !input.iter().fold(!0, |crc, byte| {
+        CRC_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(input: &[u8]) -> u32 {
+    const MODULUS: u32 = 65_521;
+
+    let (mut a, mut b) = (1, 0);
+
+    // The sums can't overflow within this many bytes, so the modulus only
+    // needs to be taken once per chunk.
+    for chunk in input.chunks(5552) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, Encoding};
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let available = Encoding::COMPRESSIBLE;
+
+        assert_eq!(
+            Some(Encoding::Gzip),
+            Encoding::negotiate(b"gzip, deflate, br", available)
+        );
+        assert_eq!(
+            Some(Encoding::Deflate),
+            Encoding::negotiate(b"gzip;q=0.5, deflate", available)
+        );
+        assert_eq!(
+            Some(Encoding::Gzip),
+            Encoding::negotiate(b"*;q=0.1, deflate;q=0", available)
+        );
+        assert_eq!(None, Encoding::negotiate(b"gzip;q=0, br", available));
+        assert_eq!(None, Encoding::negotiate(b"identity", available));
+        assert_eq!(None, Encoding::negotiate(b"", available));
+        assert_eq!(
+            Some(Encoding::Brotli),
+            Encoding::negotiate(b"gzip, br, zstd", Encoding::PRECOMPRESSED)
+        );
+        assert_eq!(
+            Some(Encoding::Gzip),
+            Encoding::negotiate(b"gzip, br;q=0.5", &[Encoding::Brotli, Encoding::Gzip])
+        );
+    }
+}
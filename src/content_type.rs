@@ -1,6 +1,12 @@
-use core::str;
+use super::charset::Charset;
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str,
+};
 
+#[derive(Clone, Copy)]
 pub enum Extension {
+    Avif,
     Css,
     Csv,
     Epub,
@@ -58,6 +64,7 @@ impl Extension {
     /// ```
     pub fn new(extension: &str) -> Option<Self> {
         Some(match extension {
+            "avif" => Self::Avif,
             "css" => Self::Css,
             "csv" => Self::Csv,
             "epub" => Self::Epub,
@@ -102,8 +109,9 @@ impl Extension {
         })
     }
 
-    pub const fn mime(&self) -> Mime {
+    pub const fn mime(self) -> Mime {
         match self {
+            Self::Avif => Mime::ImageAvif,
             Self::Css => Mime::TextCss,
             Self::Csv => Mime::TextCsv,
             Self::Epub => Mime::ApplicationEpub,
@@ -148,7 +156,7 @@ impl Extension {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Mime {
     ApplicationEpub,
     ApplicationJavascript,
@@ -174,6 +182,7 @@ pub enum Mime {
     FontTtf,
     FontWoff,
     FontWoff2,
+    ImageAvif,
     ImageGif,
     ImageJpeg,
     ImagePng,
@@ -196,14 +205,68 @@ pub enum Mime {
 
 impl Mime {
     pub fn from_input(input: &[u8]) -> Self {
-        if str::from_utf8(input).is_ok() {
+        if str::from_utf8(input).is_ok() || Charset::from_bom(input).is_some() {
             Self::TextPlain
         } else {
             Self::OctetStream
         }
     }
 
-    pub const fn name(&self) -> &'static str {
+    /// Whether content of the type is worth compressing, as opposed to
+    /// formats that are already compressed such as most images, audio, video,
+    /// and archives.
+    pub const fn is_compressible(self) -> bool {
+        matches!(
+            self,
+            Self::ApplicationJavascript
+                | Self::ApplicationLdJson
+                | Self::ApplicationJson
+                | Self::ApplicationVndMsExcel
+                | Self::ApplicationPhp
+                | Self::ApplicationPerl
+                | Self::AppicationSh
+                | Self::ApplicationTar
+                | Self::ApplicationXhtml
+                | Self::AudioWav
+                | Self::FontOtf
+                | Self::FontTtf
+                | Self::ImageIco
+                | Self::ImageSvg
+                | Self::ImageTiff
+                | Self::TextCalendar
+                | Self::TextCss
+                | Self::TextCsv
+                | Self::TextHtml
+                | Self::TextMarkdown
+                | Self::TextPlain
+                | Self::TextXml
+        )
+    }
+
+    /// Whether content of the type is text, which has a charset.
+    pub const fn is_text(self) -> bool {
+        matches!(
+            self,
+            Self::ApplicationJavascript
+                | Self::TextCalendar
+                | Self::TextCss
+                | Self::TextCsv
+                | Self::TextHtml
+                | Self::TextMarkdown
+                | Self::TextPlain
+                | Self::TextXml
+        )
+    }
+
+    /// Content type of the type with a charset parameter, if any.
+    pub const fn with_charset(self, charset: Option<Charset>) -> ContentType {
+        ContentType {
+            charset,
+            mime: self,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
         match self {
             Self::ApplicationEpub => "application/epub+zip",
             Self::ApplicationJavascript => "application/javascript",
@@ -231,6 +294,7 @@ impl Mime {
             Self::FontTtf => "font/ttf",
             Self::FontWoff => "font/woff",
             Self::FontWoff2 => "font/woff2",
+            Self::ImageAvif => "image/avif",
             Self::ImageGif => "image/gif",
             Self::ImageJpeg => "image/jpeg",
             Self::ImagePng => "image/png",
@@ -253,17 +317,58 @@ impl Mime {
     }
 }
 
+/// MIME type with its parameters, such as `text/html; charset=utf-8`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ContentType {
+    charset: Option<Charset>,
+    mime: Mime,
+}
+
+impl Display for ContentType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.mime.name())?;
+
+        if let Some(charset) = self.charset {
+            f.write_str("; charset=")?;
+            f.write_str(charset.name())?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Extension, Mime};
+    use crate::charset::Charset;
 
     #[test]
     fn test_valid() {
         assert_eq!(
             Some(Mime::TextHtml),
-            Extension::new("html").map(|e| e.mime())
+            Extension::new("html").map(Extension::mime)
+        );
+        assert!(Extension::new("rs").map(Extension::mime).is_none());
+    }
+
+    #[test]
+    fn test_compressible() {
+        assert!(Mime::TextCss.is_compressible());
+        assert!(Mime::ImageSvg.is_compressible());
+        assert!(!Mime::ImagePng.is_compressible());
+        assert!(!Mime::FontWoff2.is_compressible());
+        assert!(!Mime::ApplicationZip.is_compressible());
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(
+            "text/html; charset=utf-8",
+            Mime::TextHtml.with_charset(Some(Charset::Utf8)).to_string()
         );
-        assert!(Extension::new("rs").map(|e| e.mime()).is_none());
+        assert_eq!("image/png", Mime::ImagePng.with_charset(None).to_string());
+        assert_eq!(Mime::TextPlain, Mime::from_input(b"\xfe\xff\x00a"));
+        assert_eq!(Mime::OctetStream, Mime::from_input(b"\x00\xff"));
     }
 
     #[test]
@@ -0,0 +1,282 @@
+use super::{
+    custom_headers::is_token,
+    response::{Headers, Response, Status},
+};
+use std::str;
+
+/// Origins allowed to make cross-origin requests.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Origins {
+    /// Any origin, allowed with a `*`.
+    Any,
+    /// Origins that are exactly one of these, such as
+    /// `http://localhost:3000`.
+    List(Vec<String>),
+    /// Any origin, allowed by echoing it back.
+    Reflect,
+}
+
+/// Cross-origin resource sharing policy.
+#[derive(Clone, Debug)]
+pub struct Cors {
+    credentials: bool,
+    headers: Option<Vec<String>>,
+    max_age: Option<u64>,
+    methods: Vec<String>,
+    origins: Origins,
+}
+
+impl Cors {
+    /// Methods allowed when not configured, which are those served.
+    const METHODS: &'static [&'static str] = &["GET", "HEAD"];
+
+    /// Create a policy allowing origins that are `*` for any origin,
+    /// `reflect` for any origin echoed back, or exact origins, defaulting to
+    /// any origin.
+    ///
+    /// Methods and headers are comma separated lists, defaulting to the
+    /// methods served and any headers requested. Credentials can't be allowed
+    /// for any origin with a `*`, in which case no policy is created.
+    pub fn new(
+        origins: &[String],
+        methods: Option<&str>,
+        headers: Option<&str>,
+        credentials: bool,
+        max_age: Option<u64>,
+    ) -> Option<Self> {
+        let origins = if origins.is_empty() || origins.iter().any(|origin| origin == "*") {
+            Origins::Any
+        } else if origins.iter().any(|origin| origin == "reflect") {
+            Origins::Reflect
+        } else {
+            Origins::List(
+                origins
+                    .iter()
+                    .map(|origin| origin.trim_end_matches('/').to_owned())
+                    .collect(),
+            )
+        };
+
+        if credentials && origins == Origins::Any {
+            return None;
+        }
+
+        Some(Self {
+            credentials,
+            headers: headers.map(list),
+            max_age,
+            methods: methods.map_or_else(
+                || {
+                    Self::METHODS
+                        .iter()
+                        .map(|method| (*method).to_owned())
+                        .collect()
+                },
+                list,
+            ),
+            origins,
+        })
+    }
+
+    /// Add the fields describing whether the origin of a request may read a
+    /// response.
+    pub fn apply(&self, origin: Option<&[u8]>, headers: &mut Headers) {
+        // Unless every origin gets the same answer, the response depends on
+        // the origin.
+        if self.origins != Origins::Any {
+            headers.append("Vary", "Origin");
+        }
+
+        if let Some(allowed) = origin.and_then(|origin| self.allowed_origin(origin)) {
+            headers.append("Access-Control-Allow-Origin", allowed);
+
+            if self.credentials {
+                headers.append("Access-Control-Allow-Credentials", "true");
+            }
+        }
+    }
+
+    /// Respond to a preflight request from an origin asking whether it may
+    /// make a request with a method and headers.
+    pub fn preflight(
+        &self,
+        origin: &[u8],
+        method: &[u8],
+        request_headers: Option<&[u8]>,
+    ) -> Response<'static> {
+        let request_headers = request_headers
+            .and_then(|headers| str::from_utf8(headers).ok())
+            .map(list)
+            .unwrap_or_default();
+        let method_allowed = self
+            .methods
+            .iter()
+            .any(|allowed| allowed.as_bytes() == method);
+        // Requested headers may be echoed back, so they must be valid names.
+        let headers_allowed = request_headers
+            .iter()
+            .all(|header| header.bytes().all(is_token))
+            && self.headers.as_ref().map_or(true, |allowed| {
+                request_headers.iter().all(|header| {
+                    allowed
+                        .iter()
+                        .any(|allowed| allowed.eq_ignore_ascii_case(header))
+                })
+            });
+        let allowed = self
+            .allowed_origin(origin)
+            .filter(|_| method_allowed && headers_allowed);
+
+        let status = if allowed.is_some() {
+            Status::NO_CONTENT
+        } else {
+            Status::FORBIDDEN
+        };
+        let mut response = Response::new(status);
+        let headers = response.headers_mut();
+        self.apply(Some(origin).filter(|_| allowed.is_some()), headers);
+        headers.append(
+            "Vary",
+            "Access-Control-Request-Method, Access-Control-Request-Headers",
+        );
+
+        if allowed.is_none() {
+            return response;
+        }
+
+        headers.append("Access-Control-Allow-Methods", self.methods.join(", "));
+
+        // Without configured headers, whichever headers are requested are
+        // allowed.
+        let allowed_headers = self.headers.as_ref().unwrap_or(&request_headers);
+
+        if !allowed_headers.is_empty() {
+            headers.append("Access-Control-Allow-Headers", allowed_headers.join(", "));
+        }
+
+        if let Some(max_age) = self.max_age {
+            headers.append("Access-Control-Max-Age", max_age.to_string());
+        }
+
+        response
+    }
+
+    /// Value of the `Access-Control-Allow-Origin` header for an origin, if
+    /// it's allowed.
+    ///
+    /// Origins with bytes that can't be in a header value, such as a bare
+    /// carriage return, are never echoed back.
+    fn allowed_origin<'a>(&'a self, origin: &'a [u8]) -> Option<&'a str> {
+        let origin = str::from_utf8(origin).ok()?;
+
+        match &self.origins {
+            Origins::Any => Some("*"),
+            Origins::List(origins) => origins
+                .iter()
+                .find(|allowed| allowed.eq_ignore_ascii_case(origin))
+                .map(String::as_str),
+            Origins::Reflect => {
+                Some(origin).filter(|origin| origin.bytes().all(|x| x.is_ascii_graphic()))
+            }
+        }
+    }
+}
+
+/// Split a comma separated list, skipping empty elements.
+fn list(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|element| !element.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cors;
+    use crate::response::{Headers, Response};
+    use std::error::Error;
+
+    fn written(response: Response<'_>) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let mut buf = Vec::new();
+        response.write(&mut buf)?;
+
+        Ok(String::from_utf8(buf)?)
+    }
+
+    #[test]
+    fn test_new() {
+        let any = Cors::new(&[], None, None, false, None);
+        assert!(any.is_some());
+        assert!(Cors::new(&["*".to_owned()], None, None, true, None).is_none());
+        assert!(Cors::new(&["reflect".to_owned()], None, None, true, None).is_some());
+    }
+
+    #[test]
+    fn test_apply() {
+        let origins = ["http://localhost:3000/".to_owned()];
+        let cors = Cors::new(&origins, None, None, true, None).unwrap();
+
+        let mut headers = Headers::default();
+        cors.apply(Some(b"http://localhost:3000"), &mut headers);
+        assert_eq!(
+            vec![
+                ("Vary", "Origin"),
+                ("Access-Control-Allow-Origin", "http://localhost:3000"),
+                ("Access-Control-Allow-Credentials", "true"),
+            ],
+            headers.iter().collect::<Vec<_>>()
+        );
+
+        let mut headers = Headers::default();
+        cors.apply(Some(b"http://evil.example"), &mut headers);
+        assert_eq!(vec![("Vary", "Origin")], headers.iter().collect::<Vec<_>>());
+
+        let any = Cors::new(&[], None, None, false, None).unwrap();
+        let mut headers = Headers::default();
+        any.apply(Some(b"http://evil.example"), &mut headers);
+        assert_eq!(Some("*"), headers.get("Access-Control-Allow-Origin"));
+        assert!(!headers.contains("Vary"));
+
+        let reflect = Cors::new(&["reflect".to_owned()], None, None, false, None).unwrap();
+        let mut headers = Headers::default();
+        reflect.apply(Some(b"http://a.example\rSet-Cookie: a=b"), &mut headers);
+        assert!(!headers.contains("Access-Control-Allow-Origin"));
+    }
+
+    #[test]
+    fn test_preflight() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let origins = ["reflect".to_owned()];
+        let cors = Cors::new(&origins, Some("GET, PUT"), None, false, Some(600)).unwrap();
+
+        let response = written(cors.preflight(
+            b"http://localhost:3000",
+            b"PUT",
+            Some(b"content-type, x-token"),
+        ))?;
+        assert!(response.starts_with("HTTP/1.1 204 No Content\r\n"));
+        assert!(response.contains("Access-Control-Allow-Origin: http://localhost:3000\r\n"));
+        assert!(response.contains("Access-Control-Allow-Methods: GET, PUT\r\n"));
+        assert!(response.contains("Access-Control-Allow-Headers: content-type, x-token\r\n"));
+        assert!(response.contains("Access-Control-Max-Age: 600\r\n"));
+
+        let response = written(cors.preflight(
+            b"http://localhost:3000",
+            b"PUT",
+            Some(b"x-token\rSet-Cookie: a=b"),
+        ))?;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(!response.contains("Set-Cookie"));
+
+        let response = written(cors.preflight(b"http://localhost:3000", b"DELETE", None))?;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(!response.contains("Access-Control-Allow-Origin"));
+
+        let cors = Cors::new(&[], None, Some("Content-Type"), false, None).unwrap();
+        let response = written(cors.preflight(b"http://a.example", b"GET", Some(b"X-Token")))?;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+
+        Ok(())
+    }
+}
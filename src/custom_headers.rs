@@ -0,0 +1,161 @@
+use super::{glob::Glob, response::Headers};
+
+/// Header fields whose values are derived from the content, which can't be
+/// overridden.
+const FRAMING: &[&str] = &["Content-Length", "Transfer-Encoding"];
+
+/// Header field added to responses, given as `Name: value`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Field {
+    name: String,
+    value: String,
+}
+
+impl Field {
+    /// Parse a field in the form `Name: value`, such as
+    /// `Cross-Origin-Opener-Policy: same-origin`.
+    pub fn new(field: &str) -> Option<Self> {
+        let (name, value) = field.split_once(':')?;
+        let value = value.trim();
+
+        if name.is_empty()
+            || !name.bytes().all(is_token)
+            || FRAMING
+                .iter()
+                .any(|framing| framing.eq_ignore_ascii_case(name))
+            || !value.bytes().all(|x| x == b' ' || x.is_ascii_graphic())
+        {
+            return None;
+        }
+
+        Some(Self {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+
+    // Retrieve a reference to the name of the field.
+    pub fn name_ref(&self) -> &str {
+        &self.name
+    }
+
+    // Retrieve a reference to the value of the field.
+    pub fn value_ref(&self) -> &str {
+        &self.value
+    }
+}
+
+/// Header field added to responses for paths matching a pattern.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    field: Field,
+    pattern: Glob,
+}
+
+impl Rule {
+    /// Parse a rule in the form `PATTERN=Name: value`, such as
+    /// `*.wasm=Cross-Origin-Resource-Policy: same-origin`.
+    pub fn new(rule: &str) -> Option<Self> {
+        let (pattern, field) = rule.split_once('=')?;
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            field: Field::new(field)?,
+            pattern: Glob::new(pattern),
+        })
+    }
+}
+
+/// Add the fields for all responses and those of every rule matching a
+/// relative path to headers, replacing any fields with the same names.
+///
+/// Responses not to a path only get the fields for all responses.
+pub fn apply(fields: &[Field], rules: &[Rule], path: Option<&str>, headers: &mut Headers) {
+    let matching = rules
+        .iter()
+        .filter(|rule| path.map_or(false, |path| rule.pattern.matches(path)))
+        .map(|rule| &rule.field);
+    let fields = fields.iter().chain(matching).collect::<Vec<_>>();
+
+    for field in &fields {
+        headers.remove(&field.name);
+    }
+
+    for field in fields {
+        headers.append(field.name.clone(), field.value.clone());
+    }
+}
+
+/// Whether a byte may be part of a header name.
+pub const fn is_token(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric()
+        || matches!(
+            byte,
+            b'!' | b'#'
+                | b'$'
+                | b'%'
+                | b'&'
+                | b'\''
+                | b'*'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, Field, Rule};
+    use crate::response::Headers;
+
+    #[test]
+    fn test_field() {
+        assert!(Field::new("Cross-Origin-Opener-Policy: same-origin").is_some());
+        assert!(Field::new("X-Empty:").is_some());
+        assert!(Field::new("same-origin").is_none());
+        assert!(Field::new(": same-origin").is_none());
+        assert!(Field::new("Bad Name: value").is_none());
+        assert!(Field::new("content-length: 0").is_none());
+        assert!(Field::new("X: y\r\nZ: w").is_none());
+        assert!(Rule::new("*.wasm=Cross-Origin-Resource-Policy: same-origin").is_some());
+        assert!(Rule::new("=X: y").is_none());
+        assert!(Rule::new("*.wasm").is_none());
+    }
+
+    #[test]
+    fn test_apply() {
+        let fields = [
+            Field::new("Cross-Origin-Opener-Policy: same-origin").unwrap(),
+            Field::new("Cross-Origin-Embedder-Policy: require-corp").unwrap(),
+        ];
+        let rules = [
+            Rule::new("*.html=Cache-Control: no-store").unwrap(),
+            Rule::new("docs/**=X-Robots-Tag: noindex").unwrap(),
+        ];
+        let mut headers = Headers::default();
+        headers.append("Cache-Control", "public, max-age=60");
+
+        apply(&fields, &rules, Some("docs/index.html"), &mut headers);
+        assert_eq!(
+            vec![
+                ("Cross-Origin-Opener-Policy", "same-origin"),
+                ("Cross-Origin-Embedder-Policy", "require-corp"),
+                ("Cache-Control", "no-store"),
+                ("X-Robots-Tag", "noindex"),
+            ],
+            headers.iter().collect::<Vec<_>>()
+        );
+
+        let mut headers = Headers::default();
+        apply(&fields, &rules, None, &mut headers);
+        assert_eq!(2, headers.iter().count());
+    }
+}
@@ -0,0 +1,229 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Current time formatted as an IMF-fixdate, formatted at most once per
+/// second.
+#[derive(Debug, Default)]
+pub struct Cache {
+    formatted: String,
+    seconds: u64,
+}
+
+impl Cache {
+    /// Retrieve the current time as an IMF-fixdate for a `Date` header.
+    pub fn now(&mut self) -> &str {
+        let now = SystemTime::now();
+        let seconds = now
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        if self.formatted.is_empty() || seconds != self.seconds {
+            self.formatted = format(now);
+            self.seconds = seconds;
+        }
+
+        &self.formatted
+    }
+}
+
+/// Format a time as an IMF-fixdate, such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// Times before the Unix epoch are formatted as the epoch.
+// Indices are bounded by the lengths of the tables.
+#[allow(clippy::cast_possible_truncation)]
+pub fn format(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let days = seconds / SECONDS_PER_DAY;
+    let (year, month, day) = civil_from_days(days);
+    let time = seconds % SECONDS_PER_DAY;
+
+    // The epoch was a Thursday.
+    let weekday = WEEKDAYS[((days + 4) % 7) as usize];
+    let month = MONTHS[(month - 1) as usize];
+
+    format!(
+        "{weekday}, {day:02} {month} {year} {:02}:{:02}:{:02} GMT",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+    )
+}
+
+/// Parse an HTTP-date in the IMF-fixdate format, or in the obsolete RFC 850
+/// or asctime formats that recipients are required to accept.
+///
+/// Dates before the Unix epoch aren't supported.
+pub fn parse(input: &[u8]) -> Option<SystemTime> {
+    let input = std::str::from_utf8(input).ok()?;
+    let mut parts = input.split_ascii_whitespace();
+    let weekday = parts.next()?;
+
+    let (year, month, day, time) = if weekday.ends_with(',') {
+        let date = parts.next()?;
+
+        if date.contains('-') {
+            // RFC 850: `Sunday, 06-Nov-94 08:49:37 GMT`.
+            let mut date = date.split('-');
+            let day = date.next()?.parse().ok()?;
+            let month = month(date.next()?)?;
+            let year = date
+                .next()
+                .filter(|year| year.len() == 2)?
+                .parse::<u64>()
+                .ok()?;
+            let year = if year < 70 { 2000 + year } else { 1900 + year };
+
+            (year, month, day, parts.next()?)
+        } else {
+            // IMF-fixdate: `Sun, 06 Nov 1994 08:49:37 GMT`.
+            let day = date.parse().ok()?;
+            let month = month(parts.next()?)?;
+            let year = parts.next()?.parse().ok()?;
+
+            (year, month, day, parts.next()?)
+        }
+    } else {
+        // asctime: `Sun Nov  6 08:49:37 1994`.
+        let month = month(parts.next()?)?;
+        let day = parts.next()?.parse().ok()?;
+        let time = parts.next()?;
+        let year = parts.next()?.parse().ok()?;
+
+        (year, month, day, time)
+    };
+
+    if weekday.ends_with(',') && parts.next() != Some("GMT") {
+        return None;
+    }
+
+    if parts.next().is_some()
+        || !(1970..=9999).contains(&year)
+        || day == 0
+        || day > days_in_month(year, month)
+    {
+        return None;
+    }
+
+    let mut time = time.split(':').map(|part| part.parse::<u64>().ok());
+    let hour = time.next()??;
+    let minute = time.next()??;
+    let second = time.next()??;
+
+    if time.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let seconds =
+        days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second;
+
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+fn month(name: &str) -> Option<u64> {
+    MONTHS
+        .iter()
+        .position(|month| *month == name)
+        .map(|idx| idx as u64 + 1)
+}
+
+const fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Convert days since the Unix epoch to a year, month, and day.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as u64;
+
+    (year, month, day)
+}
+
+/// Convert a year, month, and day on or after the Unix epoch to days since
+/// the epoch.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+const fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let shifted_month = (month + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, parse, Cache};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_format() {
+        assert_eq!("Thu, 01 Jan 1970 00:00:00 GMT", format(UNIX_EPOCH));
+        assert_eq!(
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            format(UNIX_EPOCH + Duration::from_secs(784_111_777))
+        );
+        assert_eq!(
+            "Tue, 29 Feb 2000 23:59:59 GMT",
+            format(UNIX_EPOCH + Duration::from_secs(951_868_799))
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut cache = Cache::default();
+        let now = cache.now().to_owned();
+
+        assert!(now.ends_with(" GMT"));
+        assert!(parse(now.as_bytes()).is_some());
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = Some(UNIX_EPOCH + Duration::from_secs(784_111_777));
+
+        assert_eq!(expected, parse(b"Sun, 06 Nov 1994 08:49:37 GMT"));
+        assert_eq!(expected, parse(b"Sunday, 06-Nov-94 08:49:37 GMT"));
+        assert_eq!(expected, parse(b"Sun Nov  6 08:49:37 1994"));
+        assert_eq!(
+            Some(UNIX_EPOCH + Duration::from_secs(951_868_799)),
+            parse(b"Tue, 29 Feb 2000 23:59:59 GMT")
+        );
+
+        assert!(parse(b"Sun, 06 Nov 1994 08:49:37 PST").is_none());
+        assert!(parse(b"Sun, 30 Feb 1994 08:49:37 GMT").is_none());
+        assert!(parse(b"Sun, 06 Nov 1994 24:49:37 GMT").is_none());
+        assert!(parse(b"Sun, 06 Nov 1969 08:49:37 GMT").is_none());
+        assert!(parse(b"yesterday").is_none());
+    }
+}
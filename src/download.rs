@@ -0,0 +1,72 @@
+use super::percent;
+
+/// Whether a query asks for a file to be downloaded with a `download`
+/// parameter, such as `?download` or `?v=2&download=1`.
+pub fn requested(query: Option<&[u8]>) -> bool {
+    query.map_or(false, |query| {
+        query.split(|x| *x == b'&').any(|param| {
+            param
+                .split(|x| *x == b'=')
+                .next()
+                .map_or(false, |key| key == b"download")
+        })
+    })
+}
+
+/// Value of the `Content-Disposition` header for downloading a file as an
+/// attachment.
+///
+/// Names with characters that can't be sent in a quoted string, such as
+/// non-ASCII characters, are sent encoded in a `filename*` parameter with an
+/// ASCII approximation for older clients.
+pub fn content_disposition(file_name: &str) -> String {
+    let fallback = file_name
+        .chars()
+        .map(|c| {
+            if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    if fallback == file_name {
+        return format!("attachment; filename=\"{file_name}\"");
+    }
+
+    format!(
+        "attachment; filename=\"{fallback}\"; filename*=UTF-8''{}",
+        percent::encode_attr(file_name)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_disposition, requested};
+
+    #[test]
+    fn test_requested() {
+        assert!(requested(Some(b"download")));
+        assert!(requested(Some(b"v=2&download=1")));
+        assert!(!requested(Some(b"downloads")));
+        assert!(!requested(Some(b"v=download")));
+        assert!(!requested(None));
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(
+            "attachment; filename=\"data set.json\"",
+            content_disposition("data set.json")
+        );
+        assert_eq!(
+            "attachment; filename=\"r_sum_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf",
+            content_disposition("résumé.pdf")
+        );
+        assert_eq!(
+            "attachment; filename=\"a_b_.txt\"; filename*=UTF-8''a%22b%5C.txt",
+            content_disposition("a\"b\\.txt")
+        );
+    }
+}
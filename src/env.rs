@@ -1,3 +1,17 @@
+use super::{
+    cache_control::Rule as CacheRule,
+    charset::Charset,
+    cors::Cors,
+    custom_headers::{Field as HeaderField, Rule as HeaderRule},
+    error_page::Page as ErrorPage,
+    etag::Mode as ETagMode,
+    file_cache,
+    glob::Glob,
+    redirects::{self, Rule as RedirectRule, RuleError as RedirectError},
+    request,
+    response::Headers,
+    security::{self, Override as SecurityOverride, Preset as SecurityPreset},
+};
 use core::{
     fmt::{Display, Formatter, Result as FmtResult},
     num::ParseIntError,
@@ -5,7 +19,8 @@ use core::{
 use std::{
     env,
     error::Error,
-    io::Error as IoError,
+    fs,
+    io::{Error as IoError, ErrorKind},
     net::{IpAddr, Ipv4Addr},
     path::{Path, PathBuf},
     process,
@@ -25,29 +40,198 @@ const HELP: &str = concat!(
 
 FLAGS:
 
+    --clean-urls    Serve HTML pages for paths without their .html extension.
+    --clean-urls-redirect
+                    Serve clean URLs and redirect requests naming HTML pages to
+                    them.
+    --compress      Compress responses with gzip or deflate when accepted.
+    --cors          Allow cross-origin requests and answer CORS preflight
+                    requests.
+    --cors-credentials
+                    Allow cross-origin requests with credentials, which
+                    requires --cors-origin.
+    --early-hints   Send 103 Early Hints responses for HTML pages.
     --help          Print help information.
-    --index         Enable indexing for browser directory viewing.
+    --index         Enable indexing for browser directory viewing when a
+                    directory has no index file.
+    --multiviews    Serve the variant of a path that best matches the Accept
+                    and Accept-Language headers, such as guide.en.html for
+                    guide or hero.webp for hero.png.
+    --no-server-header
+                    Don't send the Server header with the version of picoserve.
 
 OPTIONS:
 
+    --cache <RULE>  Cache-Control value for request paths matching a pattern,
+                    such as '*.html=no-cache'. May be provided multiple times;
+                    the first matching rule is used.
+    --cache-max-file <SIZE>
+                    Size of the largest file kept in the in-memory cache, such
+                    as '256KiB'. Defaults to 1MiB.
+    --cache-size <SIZE>
+                    Keep up to a size of small files in memory, such as
+                    '64MiB', along with their compressed variants.
+    --compress-min-size <BYTES>
+                    Size below which files aren't compressed. Defaults to 1024.
+    --cors-headers <LIST>
+                    Comma separated request headers allowed for cross-origin
+                    requests. Defaults to any headers requested.
+    --cors-max-age <SECONDS>
+                    How long browsers may cache preflight responses.
+    --cors-methods <LIST>
+                    Comma separated methods allowed for cross-origin requests.
+                    Defaults to 'GET, HEAD'.
+    --cors-origin <ORIGIN>
+                    Origin allowed to make cross-origin requests, such as
+                    'http://localhost:3000', '*' for any origin, or 'reflect'
+                    to echo back the requesting origin. May be provided
+                    multiple times. Defaults to any origin.
+    --default-charset <CHARSET>
+                    Charset of text files that aren't valid UTF-8 and don't
+                    declare one, such as 'windows-1252'.
     --dir <PATH>    Path of the directory to serve.
+    --download-glob <PATTERN>
+                    Always send files matching a pattern, such as '*.bin', as
+                    attachments to be downloaded. May be provided multiple
+                    times.
+    --error-page <RULE>
+                    Page served for an error status, such as
+                    '404=errors/404.html'. May be provided multiple times.
+                    Defaults to 404.html style pages and 50x.html.
+    --etag <MODE>   How to generate ETags: strong, weak, hash, or off.
+                    Defaults to strong tags from file metadata.
+    --header <FIELD>
+                    Header to add to every response, such as
+                    'Cross-Origin-Opener-Policy: same-origin'. May be provided
+                    multiple times.
     --host <IP>     IP address of the host to bind to.
-    --port <NUMBER> Port to bind to."
+    --hsts <SECONDS>
+                    Send Strict-Transport-Security with a max age, for serving
+                    behind a TLS proxy.
+    --index-file <NAME>
+                    Name of a file served for requests to its directory. May
+                    be provided multiple times; the first that exists is used.
+                    Defaults to index.html and index.htm.
+    --path-header <RULE>
+                    Header to add to responses for request paths matching a
+                    pattern, such as
+                    '*.wasm=Cross-Origin-Resource-Policy: same-origin'. May be
+                    provided multiple times.
+    --port <NUMBER> Port to bind to.
+    --redirect <RULE>
+                    Redirect or rewrite requests for paths matching a pattern,
+                    such as '/blog/:slug /posts/:slug 301'. May be provided
+                    multiple times; rules are checked before those of a
+                    _redirects file in the served directory, and the first
+                    matching rule is used.
+    --security-header <FIELD>
+                    Replace a header of the security preset, such as
+                    'X-Frame-Options: SAMEORIGIN', or remove it with an empty
+                    value. May be provided multiple times.
+    --security-headers <PRESET>
+                    Security headers to send: strict, relaxed, or off.
+                    Defaults to off, which only sends X-Content-Type-Options.
+    --spa [FALLBACK]
+                    Serve a fallback page for requests accepting HTML that
+                    don't match a file, for single-page applications with
+                    client-side routing. Defaults to index.html, which must
+                    be a file within the served directory."
 );
 
+/// Names of files served for requests to their directory by default.
+const INDEX_FILES: &[&str] = &["index.html", "index.htm"];
+
 #[derive(Debug)]
 pub enum EnvironmentError {
+    CacheMaxFileInvalid { size: String },
+    CacheRuleInvalid { rule: String },
+    CacheSizeInvalid { size: String },
+    CompressMinSizeNotInteger { size: String, source: ParseIntError },
+    CorsCredentialsWithAnyOrigin,
+    CorsMaxAgeNotInteger { age: String, source: ParseIntError },
     CurrentDirectoryInvalid { source: IoError },
+    DefaultCharsetInvalid { charset: String },
+    ErrorPageInvalid { rule: String },
+    ETagModeInvalid { mode: String },
+    HeaderInvalid { header: String },
+    HeaderRuleInvalid { rule: String },
+    HstsMaxAgeNotInteger { age: String, source: ParseIntError },
     NoMatchingValue { name: String },
     PortNotInteger { port: String, source: ParseIntError },
+    RedirectInvalid { rule: String, source: RedirectError },
+    RedirectsFileInvalid { line: usize, source: RedirectError },
+    RedirectsFileUnreadable { source: IoError },
+    SecurityHeaderInvalid { header: String },
+    SecurityPresetInvalid { preset: String },
+    SpaFallbackInvalid { fallback: String },
 }
 
 impl Display for EnvironmentError {
+    // Every variant is described by its own arm of a single match.
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
+            Self::CacheMaxFileInvalid { size } => {
+                f.write_str("provided cache max file size '")?;
+                f.write_str(size)?;
+                f.write_str("' is not a valid size")
+            }
+            Self::CacheRuleInvalid { rule } => {
+                f.write_str("provided cache rule '")?;
+                f.write_str(rule)?;
+                f.write_str("' is not in the form 'PATTERN=VALUE'")
+            }
+            Self::CacheSizeInvalid { size } => {
+                f.write_str("provided cache size '")?;
+                f.write_str(size)?;
+                f.write_str("' is not a valid size")
+            }
+            Self::CompressMinSizeNotInteger { size, .. } => {
+                f.write_str("provided minimum compression size '")?;
+                f.write_str(size)?;
+                f.write_str("' is not a valid integer")
+            }
+            Self::CorsCredentialsWithAnyOrigin => {
+                f.write_str("cross-origin credentials can't be allowed for any origin with '*'")
+            }
+            Self::CorsMaxAgeNotInteger { age, .. } => {
+                f.write_str("provided cors max age '")?;
+                f.write_str(age)?;
+                f.write_str("' is not a valid integer")
+            }
             Self::CurrentDirectoryInvalid { .. } => {
                 f.write_str("current directory is invalid or does not exist")
             }
+            Self::DefaultCharsetInvalid { charset } => {
+                f.write_str("provided default charset '")?;
+                f.write_str(charset)?;
+                f.write_str("' is not a supported charset")
+            }
+            Self::ErrorPageInvalid { rule } => {
+                f.write_str("provided error page '")?;
+                f.write_str(rule)?;
+                f.write_str("' is not in the form 'STATUS=PATH' with an error status")
+            }
+            Self::ETagModeInvalid { mode } => {
+                f.write_str("provided etag mode '")?;
+                f.write_str(mode)?;
+                f.write_str("' is not one of strong, weak, hash, or off")
+            }
+            Self::HeaderInvalid { header } => {
+                f.write_str("provided header '")?;
+                f.write_str(header)?;
+                f.write_str("' is not in the form 'Name: value'")
+            }
+            Self::HeaderRuleInvalid { rule } => {
+                f.write_str("provided header rule '")?;
+                f.write_str(rule)?;
+                f.write_str("' is not in the form 'PATTERN=Name: value'")
+            }
+            Self::HstsMaxAgeNotInteger { age, .. } => {
+                f.write_str("provided hsts max age '")?;
+                f.write_str(age)?;
+                f.write_str("' is not a valid integer")
+            }
             Self::NoMatchingValue { name, .. } => {
                 f.write_str("flag '")?;
                 f.write_str(name)?;
@@ -58,38 +242,254 @@ impl Display for EnvironmentError {
                 f.write_str(port)?;
                 f.write_str("' is not a valid integer")
             }
+            Self::RedirectInvalid { rule, source } => {
+                f.write_str("provided redirect '")?;
+                f.write_str(rule)?;
+                f.write_str("' is invalid: ")?;
+                Display::fmt(source, f)
+            }
+            Self::RedirectsFileInvalid { line, source } => {
+                f.write_str(redirects::FILE_NAME)?;
+                f.write_str(" rule on line ")?;
+                Display::fmt(line, f)?;
+                f.write_str(" is invalid: ")?;
+                Display::fmt(source, f)
+            }
+            Self::RedirectsFileUnreadable { .. } => {
+                f.write_str(redirects::FILE_NAME)?;
+                f.write_str(" file could not be read")
+            }
+            Self::SecurityHeaderInvalid { header } => {
+                f.write_str("provided security header '")?;
+                f.write_str(header)?;
+                f.write_str("' is not in the form 'Name: value' for a header of the presets")
+            }
+            Self::SecurityPresetInvalid { preset } => {
+                f.write_str("provided security preset '")?;
+                f.write_str(preset)?;
+                f.write_str("' is not one of strict, relaxed, or off")
+            }
+            Self::SpaFallbackInvalid { fallback } => {
+                f.write_str("provided spa fallback '")?;
+                f.write_str(fallback)?;
+                f.write_str("' is not a file within the served directory")
+            }
         }
     }
 }
 
-impl Error for EnvironmentError {}
+impl Error for EnvironmentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::CompressMinSizeNotInteger { source, .. }
+            | Self::CorsMaxAgeNotInteger { source, .. }
+            | Self::HstsMaxAgeNotInteger { source, .. }
+            | Self::PortNotInteger { source, .. } => Some(source),
+            Self::CurrentDirectoryInvalid { source } | Self::RedirectsFileUnreadable { source } => {
+                Some(source)
+            }
+            Self::RedirectInvalid { source, .. } | Self::RedirectsFileInvalid { source, .. } => {
+                Some(source)
+            }
+            Self::CacheMaxFileInvalid { .. }
+            | Self::CacheRuleInvalid { .. }
+            | Self::CacheSizeInvalid { .. }
+            | Self::CorsCredentialsWithAnyOrigin
+            | Self::DefaultCharsetInvalid { .. }
+            | Self::ErrorPageInvalid { .. }
+            | Self::ETagModeInvalid { .. }
+            | Self::HeaderInvalid { .. }
+            | Self::HeaderRuleInvalid { .. }
+            | Self::NoMatchingValue { .. }
+            | Self::SecurityHeaderInvalid { .. }
+            | Self::SecurityPresetInvalid { .. }
+            | Self::SpaFallbackInvalid { .. } => None,
+        }
+    }
+}
 
+// Flags are independent options rather than states.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Debug)]
 pub struct Environment {
+    cache_max_file: u64,
+    cache_rules: Vec<CacheRule>,
+    cache_size: u64,
+    clean_urls: bool,
+    clean_urls_redirect: bool,
+    compress: bool,
+    compress_min_size: u64,
+    cors: Option<Cors>,
+    default_charset: Option<Charset>,
     dir: PathBuf,
+    download_globs: Vec<Glob>,
+    early_hints: bool,
+    error_pages: Vec<ErrorPage>,
+    etag: ETagMode,
+    header_rules: Vec<HeaderRule>,
+    headers: Vec<HeaderField>,
     host: IpAddr,
     index: bool,
+    index_files: Vec<String>,
+    multiviews: bool,
     port: u16,
+    redirects: Vec<RedirectRule>,
+    security_headers: Headers,
+    server_header: bool,
+    spa: Option<String>,
 }
 
 impl Environment {
     pub fn new() -> Result<Self, EnvironmentError> {
-        let mut args = env::args().skip(1);
+        Self::from_args(env::args().skip(1))
+    }
+
+    /// Parse the environment from command line arguments, excluding the name
+    /// of the program.
+    // Every flag is handled by its own arm of a single match.
+    #[allow(clippy::too_many_lines)]
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<Self, EnvironmentError> {
+        let mut args = args.peekable();
 
+        let mut cache_max_file = 1 << 20;
+        let mut cache_rules = Vec::new();
+        let mut cache_size = 0;
+        let mut clean_urls = false;
+        let mut clean_urls_redirect = false;
+        let mut compress = false;
+        let mut compress_min_size = 1024;
+        let mut cors = false;
+        let mut cors_credentials = false;
+        let mut cors_headers: Option<String> = None;
+        let mut cors_max_age: Option<u64> = None;
+        let mut cors_methods: Option<String> = None;
+        let mut cors_origins = Vec::new();
+        let mut default_charset = None;
         let mut dir: Option<PathBuf> = None;
+        let mut download_globs = Vec::new();
+        let mut early_hints = false;
+        let mut error_pages = Vec::new();
+        let mut etag = ETagMode::Strong;
+        let mut header_rules = Vec::new();
+        let mut headers = Vec::new();
         let mut host: Option<IpAddr> = None;
+        let mut hsts: Option<u64> = None;
         let mut index = false;
+        let mut index_files = Vec::new();
+        let mut multiviews = false;
         let mut port: Option<u16> = None;
+        let mut redirects = Vec::new();
+        let mut security_overrides = Vec::new();
+        let mut security_preset = SecurityPreset::Off;
+        let mut spa = None;
+        let mut server_header = true;
 
         while let Some(name) = args.next() {
             match name.as_ref() {
+                "--cache" => {
+                    let value = value(&mut args, name)?;
+                    let rule = CacheRule::new(&value)
+                        .ok_or(EnvironmentError::CacheRuleInvalid { rule: value })?;
+
+                    cache_rules.push(rule);
+                }
+                "--cache-max-file" => {
+                    let value = value(&mut args, name)?;
+
+                    cache_max_file = file_cache::parse_size(&value)
+                        .ok_or(EnvironmentError::CacheMaxFileInvalid { size: value })?;
+                }
+                "--cache-size" => {
+                    let value = value(&mut args, name)?;
+
+                    cache_size = file_cache::parse_size(&value)
+                        .ok_or(EnvironmentError::CacheSizeInvalid { size: value })?;
+                }
+                "--clean-urls" => {
+                    clean_urls = true;
+                }
+                "--clean-urls-redirect" => {
+                    clean_urls = true;
+                    clean_urls_redirect = true;
+                }
+                "--compress" => {
+                    compress = true;
+                }
+                "--compress-min-size" => {
+                    let value = value(&mut args, name)?;
+
+                    compress_min_size = value.parse().map_err(|source| {
+                        EnvironmentError::CompressMinSizeNotInteger {
+                            size: value,
+                            source,
+                        }
+                    })?;
+                }
+                "--cors" => {
+                    cors = true;
+                }
+                "--cors-credentials" => {
+                    cors_credentials = true;
+                }
+                "--cors-headers" => {
+                    cors_headers = Some(value(&mut args, name)?);
+                }
+                "--cors-max-age" => {
+                    let value = value(&mut args, name)?;
+
+                    cors_max_age = Some(value.parse().map_err(|source| {
+                        EnvironmentError::CorsMaxAgeNotInteger { age: value, source }
+                    })?);
+                }
+                "--cors-methods" => {
+                    cors_methods = Some(value(&mut args, name)?);
+                }
+                "--cors-origin" => {
+                    cors_origins.push(value(&mut args, name)?);
+                }
+                "--default-charset" => {
+                    let value = value(&mut args, name)?;
+
+                    default_charset = Some(
+                        Charset::new(&value)
+                            .ok_or(EnvironmentError::DefaultCharsetInvalid { charset: value })?,
+                    );
+                }
                 "--dir" => {
                     let value = value(&mut args, name)?;
 
                     dir = Some(PathBuf::from(value));
                 }
+                "--download-glob" => {
+                    let value = value(&mut args, name)?;
+
+                    download_globs.push(Glob::new(&value));
+                }
+                "--early-hints" => {
+                    early_hints = true;
+                }
+                "--error-page" => {
+                    let value = value(&mut args, name)?;
+                    let page = ErrorPage::new(&value)
+                        .ok_or(EnvironmentError::ErrorPageInvalid { rule: value })?;
+
+                    error_pages.push(page);
+                }
+                "--etag" => {
+                    let value = value(&mut args, name)?;
+
+                    etag = ETagMode::new(&value)
+                        .ok_or(EnvironmentError::ETagModeInvalid { mode: value })?;
+                }
+                "--header" => {
+                    let value = value(&mut args, name)?;
+                    let header = HeaderField::new(&value)
+                        .ok_or(EnvironmentError::HeaderInvalid { header: value })?;
+
+                    headers.push(header);
+                }
                 "--help" => {
-                    println!("{}", HELP);
+                    println!("{HELP}");
 
                     process::exit(0);
                 }
@@ -98,9 +498,32 @@ impl Environment {
 
                     host = Some(value.parse().unwrap());
                 }
+                "--hsts" => {
+                    let value = value(&mut args, name)?;
+
+                    hsts = Some(value.parse().map_err(|source| {
+                        EnvironmentError::HstsMaxAgeNotInteger { age: value, source }
+                    })?);
+                }
                 "--index" => {
                     index = true;
                 }
+                "--index-file" => {
+                    index_files.push(value(&mut args, name)?);
+                }
+                "--multiviews" => {
+                    multiviews = true;
+                }
+                "--no-server-header" => {
+                    server_header = false;
+                }
+                "--path-header" => {
+                    let value = value(&mut args, name)?;
+                    let rule = HeaderRule::new(&value)
+                        .ok_or(EnvironmentError::HeaderRuleInvalid { rule: value })?;
+
+                    header_rules.push(rule);
+                }
                 "--port" => {
                     let value = value(&mut args, name)?;
 
@@ -114,6 +537,37 @@ impl Environment {
                                 })?,
                         );
                 }
+                "--redirect" => {
+                    let value = value(&mut args, name)?;
+                    let rule = RedirectRule::new(&value).map_err(|source| {
+                        EnvironmentError::RedirectInvalid {
+                            rule: value,
+                            source,
+                        }
+                    })?;
+
+                    redirects.push(rule);
+                }
+                "--security-header" => {
+                    let value = value(&mut args, name)?;
+                    let header = SecurityOverride::new(&value)
+                        .ok_or(EnvironmentError::SecurityHeaderInvalid { header: value })?;
+
+                    security_overrides.push(header);
+                }
+                "--security-headers" => {
+                    let value = value(&mut args, name)?;
+
+                    security_preset = SecurityPreset::new(&value)
+                        .ok_or(EnvironmentError::SecurityPresetInvalid { preset: value })?;
+                }
+                "--spa" => {
+                    // The fallback is optional, so the next argument is only
+                    // taken if it isn't a flag.
+                    let fallback = args.next_if(|value| !value.starts_with("--"));
+
+                    spa = Some(fallback.unwrap_or_else(|| "index.html".to_owned()));
+                }
                 _ => {}
             }
         }
@@ -124,19 +578,157 @@ impl Environment {
                 .map_err(|source| EnvironmentError::CurrentDirectoryInvalid { source })?,
         };
 
+        // The fallback answers requests for missing files, so it must exist
+        // as a file within the directory, like the files it stands in for.
+        if let Some(fallback) = &spa {
+            if !request::is_contained(fallback) || !dir.join(fallback).is_file() {
+                return Err(EnvironmentError::SpaFallbackInvalid {
+                    fallback: fallback.clone(),
+                });
+            }
+        }
+
+        // Rules of a _redirects file follow those provided as flags.
+        match fs::read_to_string(dir.join(redirects::FILE_NAME)) {
+            Ok(input) => redirects.extend(redirects::parse(&input).map_err(|(line, source)| {
+                EnvironmentError::RedirectsFileInvalid { line, source }
+            })?),
+            Err(source) if source.kind() == ErrorKind::NotFound => {}
+            Err(source) => return Err(EnvironmentError::RedirectsFileUnreadable { source }),
+        }
+
+        let cors = if cors {
+            Some(
+                Cors::new(
+                    &cors_origins,
+                    cors_methods.as_deref(),
+                    cors_headers.as_deref(),
+                    cors_credentials,
+                    cors_max_age,
+                )
+                .ok_or(EnvironmentError::CorsCredentialsWithAnyOrigin)?,
+            )
+        } else {
+            None
+        };
+
         Ok(Self {
+            cache_max_file,
+            cache_rules,
+            cache_size,
+            clean_urls,
+            clean_urls_redirect,
+            compress,
+            compress_min_size,
+            cors,
+            default_charset,
             dir,
-            host: host.unwrap_or_else(|| IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
+            download_globs,
+            early_hints,
+            error_pages,
+            etag,
+            header_rules,
+            headers,
+            host: host.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
             index,
+            index_files: if index_files.is_empty() {
+                INDEX_FILES.iter().map(|name| (*name).to_owned()).collect()
+            } else {
+                index_files
+            },
+            multiviews,
             port: port.unwrap_or(5555),
+            redirects,
+            security_headers: security::headers(security_preset, &security_overrides, hsts),
+            server_header,
+            spa,
         })
     }
 
+    // Size in bytes of the largest file kept in the in-memory cache.
+    pub const fn cache_max_file(&self) -> u64 {
+        self.cache_max_file
+    }
+
+    // Retrieve a reference to the rules for Cache-Control values.
+    pub fn cache_rules_ref(&self) -> &[CacheRule] {
+        &self.cache_rules
+    }
+
+    // Size in bytes of the in-memory cache of files, which is disabled if
+    // zero.
+    pub const fn cache_size(&self) -> u64 {
+        self.cache_size
+    }
+
+    // Whether to serve HTML pages for paths without their extension or index
+    // file name.
+    pub const fn clean_urls(&self) -> bool {
+        self.clean_urls
+    }
+
+    // Whether to redirect requests naming HTML pages to their clean URLs.
+    pub const fn clean_urls_redirect(&self) -> bool {
+        self.clean_urls_redirect
+    }
+
+    // Whether to compress responses on the fly.
+    pub const fn compress(&self) -> bool {
+        self.compress
+    }
+
+    // Size in bytes below which files aren't compressed.
+    pub const fn compress_min_size(&self) -> u64 {
+        self.compress_min_size
+    }
+
+    // Retrieve a reference to the policy for cross-origin requests, if
+    // enabled.
+    pub const fn cors_ref(&self) -> Option<&Cors> {
+        self.cors.as_ref()
+    }
+
+    // Charset of text files that aren't valid UTF-8 and don't declare one.
+    pub const fn default_charset(&self) -> Option<Charset> {
+        self.default_charset
+    }
+
     // Retrieve a reference to the directory to serve.
     pub fn dir_ref(&self) -> &Path {
         &self.dir
     }
 
+    // Retrieve a reference to the patterns of paths always downloaded as
+    // attachments.
+    pub fn download_globs_ref(&self) -> &[Glob] {
+        &self.download_globs
+    }
+
+    // Whether to send interim 103 Early Hints responses.
+    pub const fn early_hints(&self) -> bool {
+        self.early_hints
+    }
+
+    // Retrieve a reference to the pages served for error statuses.
+    pub fn error_pages_ref(&self) -> &[ErrorPage] {
+        &self.error_pages
+    }
+
+    // How to generate entity tags for files.
+    pub const fn etag(&self) -> ETagMode {
+        self.etag
+    }
+
+    // Retrieve a reference to the rules for headers added to matching paths.
+    pub fn header_rules_ref(&self) -> &[HeaderRule] {
+        &self.header_rules
+    }
+
+    // Retrieve a reference to the headers added to every response.
+    pub fn headers_ref(&self) -> &[HeaderField] {
+        &self.headers
+    }
+
     // Retrieve a reference to the IP address of the host to bind to.
     pub const fn host_ref(&self) -> &IpAddr {
         &self.host
@@ -147,10 +739,42 @@ impl Environment {
         self.index
     }
 
+    // Retrieve a reference to the names of files served for requests to
+    // their directory.
+    pub fn index_files_ref(&self) -> &[String] {
+        &self.index_files
+    }
+
+    // Whether to serve the variant of a path best matching a request.
+    pub const fn multiviews(&self) -> bool {
+        self.multiviews
+    }
+
     // Port to bind to.
     pub const fn port(&self) -> u16 {
         self.port
     }
+
+    // Retrieve a reference to the rules redirecting or rewriting requests.
+    pub fn redirects_ref(&self) -> &[RedirectRule] {
+        &self.redirects
+    }
+
+    // Retrieve a reference to the security headers sent with every response.
+    pub const fn security_headers_ref(&self) -> &Headers {
+        &self.security_headers
+    }
+
+    // Whether to send the Server header.
+    pub const fn server_header(&self) -> bool {
+        self.server_header
+    }
+
+    // Retrieve a reference to the path of the fallback page of a single-page
+    // application, if enabled.
+    pub fn spa_ref(&self) -> Option<&str> {
+        self.spa.as_deref()
+    }
 }
 
 fn value(args: &mut dyn Iterator<Item = String>, name: String) -> Result<String, EnvironmentError> {
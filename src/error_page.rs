@@ -0,0 +1,170 @@
+use super::{
+    accept,
+    charset::Charset,
+    content_type::Mime,
+    response::{Body, Response, Status},
+};
+use std::{fs, iter, path::Path};
+
+/// Page served as the content of error responses with a status, given as
+/// `STATUS=PATH`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Page {
+    path: String,
+    status: Status,
+}
+
+impl Page {
+    /// Parse a page in the form `STATUS=PATH`, such as `404=errors/404.html`,
+    /// where the path is relative to the served directory.
+    pub fn new(page: &str) -> Option<Self> {
+        let (status, path) = page.split_once('=')?;
+        let status = status
+            .parse()
+            .ok()
+            .map(Status::new)
+            .filter(|status| status.is_error())?;
+
+        if path.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            path: path.trim_start_matches('/').to_owned(),
+            status,
+        })
+    }
+}
+
+/// Give an error response without content a page describing the error.
+///
+/// A page configured for the status is used, followed by a `404.html` style
+/// page named after the status and then `50x.html` for server errors in the
+/// served directory. Otherwise a minimal page is generated, in HTML if the
+/// request accepts it and in plain text if not.
+pub fn apply<'a>(
+    response: Response<'a>,
+    accept: Option<&[u8]>,
+    pages: &[Page],
+    dir: &Path,
+) -> Response<'a> {
+    let status = response.status();
+
+    if !status.is_error() || !response.is_empty() {
+        return response;
+    }
+
+    let configured = pages
+        .iter()
+        .filter(|page| page.status == status)
+        .map(|page| page.path.clone());
+    let conventional = iter::once(format!("{}.html", status.code()))
+        .chain((status.code() >= 500).then(|| "50x.html".to_owned()));
+    let page = configured
+        .chain(conventional)
+        .find_map(|path| fs::read(dir.join(path)).ok());
+
+    if let Some(page) = page {
+        let charset = Charset::detect(Mime::TextHtml, &page, false, None);
+
+        return response
+            .header(
+                "Content-Type",
+                Mime::TextHtml.with_charset(charset).to_string(),
+            )
+            .body(Body::Bytes(page.into()));
+    }
+
+    let description = format!("{} {}", status.code(), status.reason());
+    let html = accept.map_or(false, |accept| accept::includes(accept, "text/html"));
+    let (mime, content) = if html {
+        (
+            Mime::TextHtml,
+            format!("<!DOCTYPE html><title>{description}</title><h1>{description}</h1>\n"),
+        )
+    } else {
+        (Mime::TextPlain, format!("{description}\n"))
+    };
+
+    response
+        .header("Vary", "Accept")
+        .header(
+            "Content-Type",
+            mime.with_charset(Some(Charset::Utf8)).to_string(),
+        )
+        .body(Body::Bytes(content.into_bytes().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, Page};
+    use crate::response::{Response, Status};
+    use std::{env, error::Error, fs};
+
+    fn written(response: Response<'_>) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let mut buf = Vec::new();
+        response.write(&mut buf)?;
+
+        Ok(String::from_utf8(buf)?)
+    }
+
+    #[test]
+    fn test_page() {
+        assert_eq!(
+            Some(Page {
+                path: "errors/404.html".to_owned(),
+                status: Status::NOT_FOUND,
+            }),
+            Page::new("404=/errors/404.html")
+        );
+        assert!(Page::new("200=ok.html").is_none());
+        assert!(Page::new("404=").is_none());
+        assert!(Page::new("errors/404.html").is_none());
+    }
+
+    #[test]
+    fn test_apply() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-error-page-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("50x.html"), "<p>down")?;
+        fs::write(dir.join("gone.html"), "<p>gone")?;
+        let pages = [Page::new("404=gone.html").unwrap()];
+
+        let response = written(apply(
+            Response::new(Status::NOT_FOUND),
+            Some(b"text/html"),
+            &pages,
+            &dir,
+        ))?;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(response.ends_with("\r\n\r\n<p>gone"));
+
+        let response = written(apply(
+            Response::new(Status::INTERNAL_SERVER_ERROR),
+            None,
+            &[],
+            &dir,
+        ))?;
+        assert!(response.ends_with("\r\n\r\n<p>down"));
+
+        let response = written(apply(
+            Response::new(Status::FORBIDDEN),
+            Some(b"text/html,*/*;q=0.8"),
+            &[],
+            &dir,
+        ))?;
+        assert!(response.contains("Content-Type: text/html; charset=utf-8\r\n"));
+        assert!(response.ends_with("<h1>403 Forbidden</h1>\n"));
+
+        let response = written(apply(Response::new(Status::FORBIDDEN), None, &[], &dir))?;
+        assert!(response.contains("Content-Type: text/plain; charset=utf-8\r\n"));
+        assert!(response.ends_with("\r\n\r\n403 Forbidden\n"));
+
+        assert!(written(apply(Response::new(Status::OK), None, &[], &dir))?
+            .ends_with("Content-Length: 0\r\n\r\n"));
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use super::compress::Encoding;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::Metadata,
+    time::UNIX_EPOCH,
+};
+
+/// How entity tags are generated for files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Strong tags from a hash of the content.
+    Hash,
+    /// Don't generate tags.
+    Off,
+    /// Strong tags from the inode, size, and modification time.
+    Strong,
+    /// Weak tags from the inode, size, and modification time.
+    Weak,
+}
+
+impl Mode {
+    pub fn new(mode: &str) -> Option<Self> {
+        Some(match mode {
+            "hash" => Self::Hash,
+            "off" => Self::Off,
+            "strong" => Self::Strong,
+            "weak" => Self::Weak,
+            _ => return None,
+        })
+    }
+}
+
+/// Entity tag identifying a version of a file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ETag {
+    tag: String,
+    weak: bool,
+}
+
+impl ETag {
+    /// Create a tag from the metadata of a file if the mode is based on it.
+    pub fn from_metadata(metadata: &Metadata, mode: Mode) -> Option<Self> {
+        let weak = match mode {
+            Mode::Strong => false,
+            Mode::Weak => true,
+            Mode::Hash | Mode::Off => return None,
+        };
+
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos());
+
+        #[cfg(unix)]
+        let tag = format!("{:x}-{:x}-{modified:x}", metadata.ino(), metadata.len());
+        #[cfg(not(unix))]
+        let tag = format!("{:x}-{modified:x}", metadata.len());
+
+        Some(Self { tag, weak })
+    }
+
+    /// Create a strong tag from a 64-bit FNV-1a hash of content.
+    pub fn from_content(content: &[u8]) -> Self {
+        let hash = content
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+
+        Self {
+            tag: format!("{hash:016x}-{:x}", content.len()),
+            weak: false,
+        }
+    }
+
+    /// Tag of the variant of the content with an encoding applied.
+    pub fn for_encoding(mut self, encoding: Option<Encoding>) -> Self {
+        if let Some(encoding) = encoding {
+            self.tag.push('-');
+            self.tag.push_str(encoding.name());
+        }
+
+        self
+    }
+
+    /// Whether any tag in an `If-Match` or `If-None-Match` list matches.
+    ///
+    /// A list of `*` matches any tag. Weak comparison considers tags equal
+    /// if their opaque values match, while strong comparison additionally
+    /// requires both to be strong.
+    pub fn matches_any(&self, list: &[u8], weak_comparison: bool) -> bool {
+        if list == b"*" {
+            return true;
+        }
+
+        let mut rest = list;
+
+        loop {
+            rest = trim_start(rest);
+
+            let weak = rest.starts_with(b"W/");
+
+            if weak {
+                rest = &rest[2..];
+            }
+
+            if rest.first() != Some(&b'"') {
+                return false;
+            }
+
+            rest = &rest[1..];
+            let end = match rest.iter().position(|byte| *byte == b'"') {
+                Some(end) => end,
+                None => return false,
+            };
+
+            let strong_enough = weak_comparison || !(weak || self.weak);
+
+            if strong_enough && &rest[..end] == self.tag.as_bytes() {
+                return true;
+            }
+
+            rest = trim_start(&rest[end + 1..]);
+
+            match rest.first() {
+                Some(b',') => rest = &rest[1..],
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl Display for ETag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+
+        f.write_str("\"")?;
+        f.write_str(&self.tag)?;
+
+        f.write_str("\"")
+    }
+}
+
+fn trim_start(input: &[u8]) -> &[u8] {
+    let start = input
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(input.len());
+
+    &input[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ETag;
+    use crate::compress::Encoding;
+
+    #[test]
+    fn test_display() {
+        let strong = ETag::from_content(b"test");
+        assert_eq!("\"f9e6e6ef197c2b25-4\"", strong.to_string());
+
+        let weak = ETag {
+            weak: true,
+            ..strong
+        };
+        assert_eq!("W/\"f9e6e6ef197c2b25-4\"", weak.to_string());
+
+        let gzip = weak.for_encoding(Some(Encoding::Gzip));
+        assert_eq!("W/\"f9e6e6ef197c2b25-4-gzip\"", gzip.to_string());
+    }
+
+    #[test]
+    fn test_matches_any() {
+        let strong = ETag {
+            tag: "abc".to_owned(),
+            weak: false,
+        };
+        let weak = ETag {
+            weak: true,
+            ..strong.clone()
+        };
+
+        assert!(strong.matches_any(b"*", false));
+        assert!(strong.matches_any(b"\"abc\"", false));
+        assert!(strong.matches_any(b"\"x\", W/\"y\",\"abc\"", false));
+        assert!(!strong.matches_any(b"\"abcd\"", false));
+        assert!(!strong.matches_any(b"W/\"abc\"", false));
+        assert!(strong.matches_any(b"W/\"abc\"", true));
+        assert!(!weak.matches_any(b"\"abc\"", false));
+        assert!(weak.matches_any(b"\"abc\"", true));
+        assert!(!strong.matches_any(b"abc", true));
+        assert!(!strong.matches_any(b"\"abc", true));
+    }
+}
@@ -0,0 +1,348 @@
+use super::{charset::Charset, compress::Encoding, content_type::Mime, etag::ETag};
+use std::{
+    collections::HashMap,
+    fs::Metadata,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+/// Number of lookups between logs of the hit and miss counters.
+const LOG_INTERVAL: u64 = 100;
+
+/// Parse a size in bytes with an optional unit, such as `1024`, `64MiB`, or
+/// `1.5MB`.
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "kib" => 1 << 10,
+        "m" | "mb" => 1_000_000,
+        "mib" => 1 << 20,
+        "g" | "gb" => 1_000_000_000,
+        "gib" => 1 << 30,
+        _ => return None,
+    };
+
+    if let Ok(number) = number.parse::<u64>() {
+        return number.checked_mul(multiplier);
+    }
+
+    // Fractional sizes are rounded down to a whole number of bytes.
+    let number = number
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())?;
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    let bytes = (number * multiplier as f64) as u64;
+
+    Some(bytes)
+}
+
+/// Content and precomputed details of a file.
+#[derive(Debug)]
+pub struct Entry {
+    charset: Option<Charset>,
+    content: Rc<[u8]>,
+    encoded: Vec<(Encoding, Rc<[u8]>)>,
+    etag: Option<ETag>,
+    len: u64,
+    mime: Mime,
+    modified: SystemTime,
+    used: u64,
+}
+
+impl Entry {
+    // Charset of the content, if it's text.
+    pub const fn charset(&self) -> Option<Charset> {
+        self.charset
+    }
+
+    // Retrieve a reference to the entity tag of the unencoded content.
+    pub const fn etag_ref(&self) -> Option<&ETag> {
+        self.etag.as_ref()
+    }
+
+    // Type of the content.
+    pub const fn mime(&self) -> Mime {
+        self.mime
+    }
+
+    // Time the file was modified when it was cached.
+    pub const fn modified(&self) -> SystemTime {
+        self.modified
+    }
+
+    /// Number of bytes of memory the content and its variants take up.
+    fn size(&self) -> u64 {
+        let encoded = self
+            .encoded
+            .iter()
+            .map(|(_, content)| content.len())
+            .sum::<usize>();
+
+        (self.content.len() + encoded) as u64
+    }
+}
+
+/// Bounded in-memory cache of the content of small files, keyed by their
+/// resolved path and evicting the least recently used files first.
+///
+/// Entries are validated against the modification time and size of their
+/// file on each lookup.
+#[derive(Debug, Default)]
+pub struct FileCache {
+    capacity: u64,
+    entries: HashMap<PathBuf, Entry>,
+    hits: u64,
+    max_file: u64,
+    misses: u64,
+    size: u64,
+    tick: u64,
+}
+
+impl FileCache {
+    /// Create a cache holding up to a number of bytes, of files up to a size.
+    ///
+    /// A capacity of zero disables the cache.
+    pub fn new(capacity: u64, max_file: u64) -> Self {
+        Self {
+            capacity,
+            max_file,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the cache is enabled, with a capacity above zero.
+    pub const fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Whether a file of a length may be cached.
+    pub const fn accepts(&self, len: u64) -> bool {
+        self.is_enabled() && len <= self.max_file && len <= self.capacity
+    }
+
+    /// Retrieve the entry of a file if it's cached and hasn't changed since,
+    /// marking it as recently used.
+    pub fn get(&mut self, path: &Path, metadata: &Metadata) -> Option<&Entry> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let fresh = self.entries.get(path).map(|entry| {
+            Some(entry.modified) == metadata.modified().ok() && entry.len == metadata.len()
+        });
+
+        if fresh == Some(false) {
+            self.remove(path);
+        }
+
+        if fresh == Some(true) {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        if (self.hits + self.misses) % LOG_INTERVAL == 0 {
+            println!(
+                "= File cache: {} hits, {} misses, {} files in {} of {} bytes",
+                self.hits,
+                self.misses,
+                self.entries.len(),
+                self.size,
+                self.capacity
+            );
+        }
+
+        self.tick += 1;
+        let tick = self.tick;
+
+        self.entries
+            .get_mut(path)
+            .filter(|_| fresh == Some(true))
+            .map(|entry| {
+                entry.used = tick;
+
+                &*entry
+            })
+    }
+
+    /// Cache the content of a file along with its details, evicting the
+    /// least recently used files to make room.
+    ///
+    /// Files that are too large or without a modification time aren't
+    /// cached.
+    pub fn insert(
+        &mut self,
+        path: &Path,
+        metadata: &Metadata,
+        content: &[u8],
+        mime: Mime,
+        charset: Option<Charset>,
+        etag: Option<ETag>,
+    ) {
+        let modified = match metadata.modified() {
+            Ok(modified) if self.accepts(content.len() as u64) => modified,
+            _ => return,
+        };
+
+        self.remove(path);
+        self.tick += 1;
+        let entry = Entry {
+            charset,
+            content: content.into(),
+            encoded: Vec::new(),
+            etag,
+            len: metadata.len(),
+            mime,
+            modified,
+            used: self.tick,
+        };
+
+        self.make_room(entry.size());
+        self.size += entry.size();
+        let _ = self.entries.insert(path.to_owned(), entry);
+    }
+
+    /// Cached content of a file with an encoding applied, shared rather than
+    /// copied, compressing it and caching the result if it hasn't been
+    /// already.
+    pub fn content(&mut self, path: &Path, encoding: Option<Encoding>) -> Option<Rc<[u8]>> {
+        let entry = self.entries.get(path)?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => return Some(Rc::clone(&entry.content)),
+        };
+
+        if let Some((_, content)) = entry.encoded.iter().find(|(x, _)| *x == encoding) {
+            return Some(Rc::clone(content));
+        }
+
+        let content = Rc::<[u8]>::from(encoding.encode(&entry.content)?);
+        let size = content.len() as u64;
+
+        if self.size + size > self.capacity {
+            return Some(content);
+        }
+
+        self.size += size;
+        self.entries
+            .get_mut(path)?
+            .encoded
+            .push((encoding, Rc::clone(&content)));
+
+        Some(content)
+    }
+
+    /// Evict the least recently used files until there's room for a number
+    /// of bytes.
+    fn make_room(&mut self, size: u64) {
+        while self.size + size > self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(path, _)| path.clone());
+
+            match oldest {
+                Some(path) => self.remove(&path),
+                None => break,
+            }
+        }
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.size -= entry.size();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_size, FileCache};
+    use crate::{compress::Encoding, content_type::Mime};
+    use std::{env, error::Error, fs, rc::Rc};
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(Some(1024), parse_size("1024"));
+        assert_eq!(Some(64 << 20), parse_size("64MiB"));
+        assert_eq!(Some(1 << 20), parse_size("1 mib"));
+        assert_eq!(Some(1_500_000), parse_size("1.5MB"));
+        assert_eq!(Some(2000), parse_size("2k"));
+        assert!(parse_size("64 MiBs").is_none());
+        assert!(parse_size("MiB").is_none());
+        assert!(parse_size("-1").is_none());
+    }
+
+    #[test]
+    fn test_cache() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-file-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let paths = ["a.json", "b.json", "c.json"].map(|name| dir.join(name));
+
+        for path in &paths {
+            fs::write(path, "[1, 2, 3, 4]")?;
+        }
+
+        assert!(!FileCache::new(0, 12).is_enabled());
+        let mut cache = FileCache::new(24, 12);
+        assert!(cache.is_enabled());
+        let insert =
+            |cache: &mut FileCache, idx: usize| -> Result<(), Box<dyn Error + Send + Sync>> {
+                let path = &paths[idx];
+                let content = fs::read(path)?;
+                cache.insert(
+                    path,
+                    &fs::metadata(path)?,
+                    &content,
+                    Mime::ApplicationJson,
+                    None,
+                    None,
+                );
+
+                Ok(())
+            };
+
+        insert(&mut cache, 0)?;
+        insert(&mut cache, 1)?;
+        assert!(cache.get(&paths[0], &fs::metadata(&paths[0])?).is_some());
+
+        // The least recently used file makes room for the next.
+        insert(&mut cache, 2)?;
+        assert!(cache.get(&paths[1], &fs::metadata(&paths[1])?).is_none());
+        assert!(cache.get(&paths[0], &fs::metadata(&paths[0])?).is_some());
+        let content = cache.content(&paths[2], None).unwrap();
+        assert_eq!(b"[1, 2, 3, 4]".as_ref(), &*content);
+        // Hits share the cached content rather than copying it.
+        assert!(Rc::ptr_eq(
+            &content,
+            &cache.content(&paths[2], None).unwrap()
+        ));
+
+        // Compressed variants only stay cached if there's room for them.
+        assert!(cache.content(&paths[2], Some(Encoding::Gzip)).is_some());
+        assert_eq!(24, cache.size);
+
+        // Entries of files that have changed are dropped.
+        fs::write(&paths[0], "[]")?;
+        assert!(cache.get(&paths[0], &fs::metadata(&paths[0])?).is_none());
+        assert_eq!(1, cache.entries.len());
+        assert_eq!((2, 2), (cache.hits, cache.misses));
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+/// Pattern matching paths relative to the served directory.
+///
+/// `*` matches any characters within a path segment, `**` matches across
+/// segments, and `?` matches a single character other than `/`. Patterns
+/// without a `/` are matched against the file name alone, so `*.html`
+/// matches HTML files in any directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Glob {
+    file_name: bool,
+    pattern: String,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let pattern = pattern.trim_start_matches('/');
+
+        Self {
+            file_name: !pattern.contains('/'),
+            pattern: pattern.to_owned(),
+        }
+    }
+
+    /// Whether the pattern matches a relative path.
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.trim_start_matches('/');
+        let path = if self.file_name {
+            path.rsplit('/').next().unwrap_or(path)
+        } else {
+            path
+        };
+
+        matches(self.pattern.as_bytes(), path.as_bytes())
+    }
+}
+
+fn matches(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            // `**/` may also match no directories at all.
+            if let [b'/', after @ ..] = rest {
+                if matches(after, path) {
+                    return true;
+                }
+            }
+
+            (0..=path.len()).any(|idx| matches(rest, &path[idx..]))
+        }
+        [b'*', rest @ ..] => {
+            let segment_len = path.iter().position(|x| *x == b'/').unwrap_or(path.len());
+
+            (0..=segment_len).any(|idx| matches(rest, &path[idx..]))
+        }
+        [b'?', rest @ ..] => matches!(path, [x, tail @ ..] if *x != b'/' && matches(rest, tail)),
+        [expected, rest @ ..] => {
+            matches!(path, [x, tail @ ..] if x == expected && matches(rest, tail))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Glob;
+
+    #[test]
+    fn test_file_name() {
+        let glob = Glob::new("*.html");
+
+        assert!(glob.matches("index.html"));
+        assert!(glob.matches("docs/a/index.html"));
+        assert!(!glob.matches("index.htm"));
+        assert!(!glob.matches("index.html/a.js"));
+    }
+
+    #[test]
+    fn test_path() {
+        let glob = Glob::new("/assets/**");
+        assert!(glob.matches("assets/app.js"));
+        assert!(glob.matches("/assets/img/a.png"));
+        assert!(!glob.matches("static/assets/app.js"));
+
+        let glob = Glob::new("docs/*/?.md");
+        assert!(glob.matches("docs/a/b.md"));
+        assert!(!glob.matches("docs/a/bc.md"));
+        assert!(!glob.matches("docs/a/b/c.md"));
+
+        let glob = Glob::new("**/img/*.png");
+        assert!(glob.matches("img/a.png"));
+        assert!(glob.matches("a/b/img/c.png"));
+        assert!(!glob.matches("a/img/b/c.png"));
+    }
+}
@@ -0,0 +1,311 @@
+use super::{custom_headers::Field, response::Headers};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    error::Error,
+    fs,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Name of the file of header rules in the served directory.
+pub const FILE_NAME: &str = "_headers";
+
+#[derive(Debug)]
+pub enum FileError {
+    /// A line isn't a header field in the form `Name: value`.
+    FieldInvalid { line: usize, field: String },
+    /// A header field comes before any path pattern.
+    FieldWithoutPath { line: usize },
+    /// The file exists but couldn't be read.
+    Read { source: IoError },
+}
+
+impl Display for FileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::FieldInvalid { line, field } => {
+                f.write_str(FILE_NAME)?;
+                f.write_str(" field '")?;
+                f.write_str(field)?;
+                f.write_str("' on line ")?;
+                Display::fmt(line, f)?;
+                f.write_str(" is not in the form 'Name: value' or '! Name'")
+            }
+            Self::FieldWithoutPath { line } => {
+                f.write_str(FILE_NAME)?;
+                f.write_str(" field on line ")?;
+                Display::fmt(line, f)?;
+                f.write_str(" comes before any path")
+            }
+            Self::Read { .. } => {
+                f.write_str(FILE_NAME)?;
+                f.write_str(" file could not be read")
+            }
+        }
+    }
+}
+
+impl Error for FileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Read { source } => Some(source),
+            Self::FieldInvalid { .. } | Self::FieldWithoutPath { .. } => None,
+        }
+    }
+}
+
+/// Header fields added to or removed from responses for paths matching a
+/// pattern.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Block {
+    fields: Vec<Field>,
+    pattern: String,
+    removals: Vec<String>,
+}
+
+/// Parse the blocks of a `_headers` file, each a path pattern such as
+/// `/assets/*` followed by indented `Name: value` fields to add and
+/// `! Name` fields to remove.
+///
+/// Blank lines and comments starting with `#` are skipped.
+fn parse(input: &str) -> Result<Vec<Block>, FileError> {
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('/') {
+            blocks.push(Block {
+                pattern: line.to_owned(),
+                ..Block::default()
+            });
+
+            continue;
+        }
+
+        let block = blocks
+            .last_mut()
+            .ok_or(FileError::FieldWithoutPath { line: index + 1 })?;
+        let invalid = || FileError::FieldInvalid {
+            line: index + 1,
+            field: line.to_owned(),
+        };
+
+        if let Some(name) = line.strip_prefix('!') {
+            // Removals are validated as fields without a value.
+            let name = Field::new(&format!("{}:", name.trim())).ok_or_else(invalid)?;
+
+            block.removals.push(name.name_ref().to_owned());
+        } else {
+            block.fields.push(Field::new(line).ok_or_else(invalid)?);
+        }
+    }
+
+    Ok(blocks)
+}
+
+/// Rules of the `_headers` file of the served directory, reloaded when the
+/// file changes.
+#[derive(Debug, Default)]
+pub struct HeadersFile {
+    blocks: Vec<Block>,
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl HeadersFile {
+    /// Load the `_headers` file of a directory, which has no rules if it
+    /// doesn't exist.
+    pub fn load(dir: &Path) -> Result<Self, FileError> {
+        let mut file = Self {
+            path: dir.join(FILE_NAME),
+            ..Self::default()
+        };
+        file.blocks = file.read()?;
+
+        Ok(file)
+    }
+
+    /// Reload the rules if the file was modified, created, or removed since
+    /// they were loaded.
+    ///
+    /// The previous rules are kept if the file has become invalid.
+    pub fn reload(&mut self) {
+        if stamp(&self.path) == self.stamp {
+            return;
+        }
+
+        match self.read() {
+            Ok(blocks) => {
+                self.blocks = blocks;
+                println!("= Reloaded {}", self.path.display());
+            }
+            Err(why) => eprintln!("Failed to reload {}: {why}", self.path.display()),
+        }
+    }
+
+    /// Apply the blocks matching a path relative to the served directory to
+    /// headers.
+    ///
+    /// Fields replace any of the same name, with the values of a name from
+    /// multiple blocks joined by commas, and removals apply last.
+    pub fn apply(&self, path: &str, headers: &mut Headers) {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        let matching = self
+            .blocks
+            .iter()
+            .filter(|block| matches(block.pattern.as_bytes(), path.as_bytes()))
+            .collect::<Vec<_>>();
+        let mut fields: Vec<(&str, String)> = Vec::new();
+
+        for field in matching.iter().flat_map(|block| &block.fields) {
+            if let Some((_, value)) = fields
+                .iter_mut()
+                .find(|(name, _)| name.eq_ignore_ascii_case(field.name_ref()))
+            {
+                value.push_str(", ");
+                value.push_str(field.value_ref());
+            } else {
+                fields.push((field.name_ref(), field.value_ref().to_owned()));
+            }
+        }
+
+        for (name, value) in fields {
+            headers.remove(name);
+            headers.append(name.to_owned(), value);
+        }
+
+        for name in matching.iter().flat_map(|block| &block.removals) {
+            headers.remove(name);
+        }
+    }
+
+    /// Read and parse the file, recording when it was modified.
+    fn read(&mut self) -> Result<Vec<Block>, FileError> {
+        self.stamp = stamp(&self.path);
+
+        match fs::read_to_string(&self.path) {
+            Ok(input) => parse(&input),
+            Err(source) if source.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(source) => Err(FileError::Read { source }),
+        }
+    }
+}
+
+/// Modification time and size of a file, to tell when it changed.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Whether a pattern matches a path, where `*` matches any characters and a
+/// `:name` placeholder matches a segment.
+fn matches(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', rest @ ..] => (0..=path.len()).any(|idx| matches(rest, &path[idx..])),
+        [b':', rest @ ..] => {
+            let name_len = rest
+                .iter()
+                .position(|x| !x.is_ascii_alphanumeric() && *x != b'_')
+                .unwrap_or(rest.len());
+            let segment_len = path.iter().position(|x| *x == b'/').unwrap_or(path.len());
+
+            (1..=segment_len).any(|idx| matches(&rest[name_len..], &path[idx..]))
+        }
+        [expected, rest @ ..] => {
+            matches!(path, [x, tail @ ..] if x == expected && matches(rest, tail))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matches, parse, FileError, HeadersFile, FILE_NAME};
+    use crate::response::Headers;
+    use std::{env, error::Error, fs};
+
+    #[test]
+    fn test_parse() {
+        let blocks = parse(
+            "# Production headers
+            /*
+              X-Frame-Options: DENY
+              ! X-Powered-By
+
+            /assets/*
+              Cache-Control: public, max-age=31536000
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(2, blocks.len());
+        assert_eq!(vec!["X-Powered-By".to_owned()], blocks[0].removals);
+        assert_eq!(1, blocks[1].fields.len());
+        assert!(matches!(
+            parse("X-Frame-Options: DENY"),
+            Err(FileError::FieldWithoutPath { line: 1 })
+        ));
+        assert!(matches!(
+            parse("/*\n  X-Frame-Options DENY"),
+            Err(FileError::FieldInvalid { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches(b"/*", b"/"));
+        assert!(matches(b"/*", b"/a/b.html"));
+        assert!(matches(b"/assets/*.js", b"/assets/app/main.js"));
+        assert!(matches(b"/blog/:slug/", b"/blog/launch/"));
+        assert!(!matches(b"/blog/:slug/", b"/blog/a/b/"));
+        assert!(!matches(b"/about", b"/about.html"));
+    }
+
+    #[test]
+    fn test_apply_and_reload() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-headers-file-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            dir.join(FILE_NAME),
+            "/*\n  Link: </a.css>; rel=preload\n  ! X-Robots-Tag\n\
+             /docs/*\n  Link: </b.css>; rel=preload\n  Cache-Control: no-cache\n",
+        )?;
+        let mut file = HeadersFile::load(&dir)?;
+
+        let mut headers = Headers::default();
+        headers.append("Cache-Control", "public, max-age=60");
+        headers.append("X-Robots-Tag", "noindex");
+        file.apply("docs/index.html", &mut headers);
+        assert_eq!(
+            vec![
+                ("Link", "</a.css>; rel=preload, </b.css>; rel=preload"),
+                ("Cache-Control", "no-cache"),
+            ],
+            headers.iter().collect::<Vec<_>>()
+        );
+
+        fs::write(dir.join(FILE_NAME), "/*\n  X-Reloaded: yes\n")?;
+        file.reload();
+        let mut headers = Headers::default();
+        file.apply("index.html", &mut headers);
+        assert_eq!(Some("yes"), headers.get("X-Reloaded"));
+
+        // An invalid file keeps the previous rules.
+        fs::write(dir.join(FILE_NAME), "X-Reloaded: no\n")?;
+        file.reload();
+        let mut headers = Headers::default();
+        file.apply("index.html", &mut headers);
+        assert_eq!(Some("yes"), headers.get("X-Reloaded"));
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Font file extensions that are worth preloading when referenced.
+const FONT_EXTENSIONS: &[&str] = &[".otf", ".ttf", ".woff", ".woff2"];
+
+/// Name and decoded value of an HTML attribute.
+type Attribute<'a> = (&'a [u8], Cow<'a, str>);
+
+/// Kind of resource that a hint points to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Destination {
+    Font,
+    Module,
+    Script,
+    Style,
+}
+
+impl Destination {
+    /// Parameters of a `Link` header value that preloads the destination.
+    pub const fn params(self) -> &'static str {
+        match self {
+            Self::Font => "rel=preload; as=font; crossorigin",
+            Self::Module => "rel=modulepreload",
+            Self::Script => "rel=preload; as=script",
+            Self::Style => "rel=preload; as=style",
+        }
+    }
+}
+
+/// Resource referenced by an HTML document that may be preloaded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hint {
+    destination: Destination,
+    href: String,
+}
+
+impl Hint {
+    // Kind of resource being referenced.
+    pub const fn destination(&self) -> Destination {
+        self.destination
+    }
+
+    // Retrieve a reference to the URI reference of the resource.
+    pub fn href_ref(&self) -> &str {
+        &self.href
+    }
+}
+
+impl Display for Hint {
+    /// Format the hint as the value of a `Link` header.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("<")?;
+        f.write_str(self.href_ref())?;
+        f.write_str(">; ")?;
+
+        f.write_str(self.destination().params())
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    hints: Vec<Hint>,
+    modified: SystemTime,
+}
+
+/// Cache of hints extracted from HTML files, invalidated by modification time.
+#[derive(Debug, Default)]
+pub struct Cache {
+    entries: HashMap<PathBuf, Entry>,
+}
+
+impl Cache {
+    /// Retrieve the cached hints of a file if it hasn't been modified since
+    /// they were extracted.
+    pub fn get(&self, path: &Path, modified: SystemTime) -> Option<&[Hint]> {
+        self.entries
+            .get(path)
+            .filter(|entry| entry.modified == modified)
+            .map(|entry| entry.hints.as_slice())
+    }
+
+    /// Retrieve the hints of a file, extracting them from its contents if they
+    /// aren't cached or are out of date.
+    ///
+    /// Files without a known modification time are never cached.
+    pub fn hints(
+        &mut self,
+        path: &Path,
+        modified: Option<SystemTime>,
+        content: &[u8],
+    ) -> Cow<'_, [Hint]> {
+        let modified = match modified {
+            Some(modified) => modified,
+            None => return Cow::Owned(extract(content)),
+        };
+
+        let stale = self
+            .entries
+            .get(path)
+            .map_or(true, |entry| entry.modified != modified);
+
+        if stale {
+            let _ = self.entries.insert(
+                path.to_owned(),
+                Entry {
+                    hints: extract(content),
+                    modified,
+                },
+            );
+        }
+
+        Cow::Borrowed(&self.entries[path].hints)
+    }
+}
+
+/// Extract the stylesheets, scripts, and fonts referenced by an HTML document.
+///
+/// Hints are returned in document order without duplicates.
+pub fn extract(html: &[u8]) -> Vec<Hint> {
+    let mut hints = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.iter().position(|byte| *byte == b'<') {
+        rest = &rest[start + 1..];
+
+        if rest.starts_with(b"!--") {
+            rest = skip_past(rest, b"-->");
+
+            continue;
+        }
+
+        let name_len = rest
+            .iter()
+            .position(|byte| !byte.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        let (attributes, remaining) = attributes(&rest[name_len..]);
+        rest = remaining;
+
+        if name.eq_ignore_ascii_case(b"link") {
+            link(&mut hints, &attributes);
+        } else if name.eq_ignore_ascii_case(b"script") {
+            script(&mut hints, &attributes);
+            rest = skip_past(rest, b"</script");
+        } else if name.eq_ignore_ascii_case(b"style") {
+            let end = find(rest, b"</style").unwrap_or(rest.len());
+            style(&mut hints, &rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+
+    hints
+}
+
+/// Parse the attributes of a tag, returning them and the input after the tag.
+fn attributes(mut input: &[u8]) -> (Vec<Attribute<'_>>, &[u8]) {
+    let mut attributes = Vec::new();
+
+    loop {
+        input = trim_start(input);
+
+        match input.first() {
+            None => return (attributes, input),
+            Some(b'>') => return (attributes, &input[1..]),
+            Some(b'/') => {
+                input = &input[1..];
+
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let name_len = input
+            .iter()
+            .position(|byte| byte.is_ascii_whitespace() || matches!(byte, b'=' | b'>' | b'/'))
+            .unwrap_or(input.len());
+        let name = &input[..name_len];
+        input = trim_start(&input[name_len..]);
+
+        if input.first() != Some(&b'=') {
+            attributes.push((name, Cow::Borrowed("")));
+
+            continue;
+        }
+
+        input = trim_start(&input[1..]);
+
+        let value = if let Some(quote @ (b'"' | b'\'')) = input.first() {
+            let len = input[1..]
+                .iter()
+                .position(|byte| byte == quote)
+                .unwrap_or(input.len() - 1);
+            let value = &input[1..=len];
+            input = input.get(len + 2..).unwrap_or_default();
+
+            value
+        } else {
+            let len = input
+                .iter()
+                .position(|byte| byte.is_ascii_whitespace() || *byte == b'>')
+                .unwrap_or(input.len());
+            let value = &input[..len];
+            input = &input[len..];
+
+            value
+        };
+
+        attributes.push((name, decode(value)));
+    }
+}
+
+fn attribute<'a>(attributes: &'a [Attribute<'_>], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name.as_bytes()))
+        .map(|(_, value)| value.as_ref())
+}
+
+fn link(hints: &mut Vec<Hint>, attributes: &[Attribute<'_>]) {
+    let (href, rel) = match (attribute(attributes, "href"), attribute(attributes, "rel")) {
+        (Some(href), Some(rel)) => (href, rel),
+        _ => return,
+    };
+    let has_rel = |name: &str| {
+        rel.split_ascii_whitespace()
+            .any(|token| token.eq_ignore_ascii_case(name))
+    };
+
+    let destination = if has_rel("stylesheet") && !has_rel("alternate") {
+        Destination::Style
+    } else if has_rel("modulepreload") {
+        Destination::Module
+    } else if has_rel("preload") {
+        match attribute(attributes, "as") {
+            Some(value) if value.eq_ignore_ascii_case("font") => Destination::Font,
+            Some(value) if value.eq_ignore_ascii_case("script") => Destination::Script,
+            Some(value) if value.eq_ignore_ascii_case("style") => Destination::Style,
+            _ => return,
+        }
+    } else {
+        return;
+    };
+
+    push(hints, destination, href);
+}
+
+fn script(hints: &mut Vec<Hint>, attributes: &[Attribute<'_>]) {
+    let src = match attribute(attributes, "src") {
+        Some(src) => src,
+        None => return,
+    };
+
+    let destination = match attribute(attributes, "type") {
+        Some(kind) if kind.eq_ignore_ascii_case("module") => Destination::Module,
+        _ => Destination::Script,
+    };
+
+    push(hints, destination, src);
+}
+
+/// Find fonts referenced via `url()` in an inline stylesheet.
+fn style(hints: &mut Vec<Hint>, mut css: &[u8]) {
+    while let Some(start) = find(css, b"url(") {
+        css = &css[start + 4..];
+        let end = match css.iter().position(|byte| *byte == b')') {
+            Some(end) => end,
+            None => return,
+        };
+
+        let url = String::from_utf8_lossy(&css[..end]);
+        let url = url.trim().trim_matches(|c| c == '"' || c == '\'');
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+
+        if FONT_EXTENSIONS
+            .iter()
+            .any(|extension| path.to_ascii_lowercase().ends_with(extension))
+        {
+            push(hints, Destination::Font, url);
+        }
+
+        css = &css[end..];
+    }
+}
+
+fn push(hints: &mut Vec<Hint>, destination: Destination, href: &str) {
+    if !is_linkable(href) || hints.iter().any(|hint| hint.href == href) {
+        return;
+    }
+
+    hints.push(Hint {
+        destination,
+        href: href.to_owned(),
+    });
+}
+
+/// Whether a URI reference can be written as-is into a `Link` header.
+fn is_linkable(href: &str) -> bool {
+    !href.is_empty()
+        && !href.starts_with("data:")
+        && href
+            .bytes()
+            .all(|byte| byte.is_ascii_graphic() && !matches!(byte, b'<' | b'>' | b'"'))
+}
+
+/// Decode the character references in an attribute value that appear in URLs.
+fn decode(value: &[u8]) -> Cow<'_, str> {
+    let value = String::from_utf8_lossy(value);
+
+    if value.contains("&amp;") {
+        Cow::Owned(value.replace("&amp;", "&"))
+    } else {
+        value
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+fn skip_past<'a>(input: &'a [u8], needle: &[u8]) -> &'a [u8] {
+    find(input, needle).map_or(&[], |idx| &input[idx + needle.len()..])
+}
+
+fn trim_start(input: &[u8]) -> &[u8] {
+    let start = input
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(input.len());
+
+    &input[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract, Destination};
+
+    #[test]
+    fn test_extract() {
+        let html = r#"<!DOCTYPE html>
+            <html>
+            <head>
+                <LINK rel="stylesheet" href="style.css">
+                <link rel=preload as=font href="/fonts/a.woff2" crossorigin>
+                <link rel="icon" href="favicon.ico">
+                <script src='app.js?v=1&amp;x=2'></script>
+                <script type="module" src="/main.js"></script>
+                <style>@font-face { src: url("b.woff") format("woff"); }</style>
+            </head>
+            </html>"#;
+
+        let hints = extract(html.as_bytes());
+        let hints = hints
+            .iter()
+            .map(|hint| (hint.destination(), hint.href_ref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (Destination::Style, "style.css"),
+                (Destination::Font, "/fonts/a.woff2"),
+                (Destination::Script, "app.js?v=1&x=2"),
+                (Destination::Module, "/main.js"),
+                (Destination::Font, "b.woff"),
+            ],
+            hints
+        );
+    }
+
+    #[test]
+    fn test_extract_skips() {
+        let html = r#"
+            <!-- <link rel="stylesheet" href="commented.css"> -->
+            <link rel="alternate stylesheet" href="alt.css">
+            <script>document.write('<script src="inline.js"></script>');</script>
+            <img src="data:image/png;base64,AAAA">
+            <script src="data:text/javascript,1"></script>
+            <link rel="stylesheet" href="dup.css"><link rel="stylesheet" href="dup.css">"#;
+
+        let hints = extract(html.as_bytes());
+
+        assert_eq!(1, hints.len());
+        assert_eq!(Destination::Style, hints[0].destination());
+        assert_eq!("dup.css", hints[0].href_ref());
+    }
+}
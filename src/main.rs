@@ -1,6 +1,5 @@
 #![doc = include_str!("../README.md")]
 #![deny(
-    box_pointers,
     clippy::all,
     clippy::cargo,
     clippy::complexity,
@@ -34,18 +33,45 @@
 // These lints would introduce less concise code.
 #![allow(clippy::module_name_repetitions)]
 
+mod accept;
+mod cache_control;
+mod charset;
+mod compress;
 mod content_type;
+mod cors;
+mod custom_headers;
+mod date;
+mod download;
 mod env;
+mod error_page;
+mod etag;
+mod file_cache;
+mod glob;
+mod headers_file;
+mod hints;
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+mod mmap;
+mod negotiate;
+mod percent;
+mod redirects;
 mod request;
 mod response;
+mod security;
+mod state;
 
-use self::env::{Environment, EnvironmentError};
+use self::{
+    env::{Environment, EnvironmentError},
+    file_cache::FileCache,
+    headers_file::{FileError as HeadersFileError, HeadersFile},
+    state::State,
+};
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use std::{error::Error, io::Error as IoError, net::TcpListener};
 
 #[derive(Debug)]
 enum ApplicationError {
     LoadingEnvironment { source: EnvironmentError },
+    LoadingHeadersFile { source: HeadersFileError },
     TcpBinding { source: IoError },
 }
 
@@ -53,6 +79,7 @@ impl Display for ApplicationError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             Self::LoadingEnvironment { .. } => f.write_str("failed to load from environment"),
+            Self::LoadingHeadersFile { .. } => f.write_str("failed to load headers file"),
             Self::TcpBinding { .. } => f.write_str("failed to bind to host and port"),
         }
     }
@@ -62,6 +89,7 @@ impl Error for ApplicationError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             Self::LoadingEnvironment { source } => Some(source),
+            Self::LoadingHeadersFile { source } => Some(source),
             Self::TcpBinding { source } => Some(source),
         }
     }
@@ -80,9 +108,33 @@ fn main() -> Result<(), ApplicationError> {
         println!("= Indexing directories for browser file listing");
     }
 
+    if env.compress() {
+        println!(
+            "= Compressing responses larger than {} bytes",
+            env.compress_min_size()
+        );
+    }
+
+    if env.cache_size() > 0 {
+        println!(
+            "= Caching files up to {} bytes in {} bytes of memory",
+            env.cache_max_file(),
+            env.cache_size()
+        );
+    }
+
+    if env.early_hints() {
+        println!("= Sending 103 Early Hints for HTML pages");
+    }
+
+    let headers_file = HeadersFile::load(env.dir_ref())
+        .map_err(|source| ApplicationError::LoadingHeadersFile { source })?;
+    let file_cache = FileCache::new(env.cache_size(), env.cache_max_file());
+    let mut state = State::new(headers_file, file_cache);
+
     for stream in listener.incoming() {
-        if let Err(why) = request::handle(&mut stream.unwrap(), &env) {
-            eprintln!("Failed to handle stream: {:?}", why);
+        if let Err(why) = request::handle(&mut stream.unwrap(), &env, &mut state) {
+            eprintln!("Failed to handle stream: {why:?}");
         }
     }
 
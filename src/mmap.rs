@@ -0,0 +1,122 @@
+// Mapping and unmapping memory needs calls into the C library, which can't be
+// done safely, so this module is only built with the `mmap` feature.
+#![allow(unsafe_code)]
+
+use std::{
+    ffi::c_void,
+    fs::File,
+    io::Error as IoError,
+    ops::Deref,
+    os::{raw::c_int, unix::io::AsRawFd},
+    ptr, slice,
+};
+
+/// Pages may be read.
+const PROT_READ: c_int = 1;
+
+/// Changes to the mapping aren't written back to the file.
+const MAP_PRIVATE: c_int = 2;
+
+extern "C" {
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: i64,
+    ) -> *mut c_void;
+
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+/// Read-only mapping of the start of a file into memory.
+#[derive(Debug)]
+pub struct Mmap {
+    len: usize,
+    ptr: *mut c_void,
+}
+
+impl Mmap {
+    /// Map the first bytes of a file into memory.
+    ///
+    /// Fails if the length is zero, since empty mappings aren't allowed, or
+    /// longer than the file.
+    pub fn new(file: &File, len: u64) -> Result<Self, IoError> {
+        let len = usize::try_from(len).map_err(|_| IoError::from_raw_os_error(22))?;
+
+        // Mapping past the end of the file would fault on access.
+        if len == 0 || file.metadata()?.len() < len as u64 {
+            return Err(IoError::from_raw_os_error(22));
+        }
+
+        // SAFETY: the address is left to the kernel and the descriptor is
+        // valid for the duration of the call. The mapping is private and
+        // read-only, so it doesn't alias any memory Rust knows about.
+        let ptr = unsafe {
+            mmap(
+                ptr::null_mut(),
+                len,
+                PROT_READ,
+                MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+
+        // MAP_FAILED is all bits set.
+        if ptr as usize == usize::MAX {
+            return Err(IoError::last_os_error());
+        }
+
+        Ok(Self { len, ptr })
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    /// Content of the mapping.
+    ///
+    /// Reading pages past the end of a file that's been truncated since it
+    /// was mapped raises `SIGBUS` and kills the process, so users check the
+    /// length of the file before reading each part of the mapping. A file
+    /// truncated between the check and the read still faults, which only a
+    /// signal handler could prevent. Files replaced by renaming a new file
+    /// over them are safe, since the mapping keeps the old file alive.
+    fn deref(&self) -> &[u8] {
+        // SAFETY: the mapping is valid for reads of its length until it's
+        // dropped, and is never written to.
+        unsafe { slice::from_raw_parts(self.ptr.cast::<u8>(), self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        // SAFETY: the pointer and length are those of a mapping created by
+        // `mmap` that no slices outlive.
+        let _ = unsafe { munmap(self.ptr, self.len) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mmap;
+    use std::{env, error::Error, fs, fs::File};
+
+    #[test]
+    fn test_mmap() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let path = env::temp_dir().join(format!("picoserve-mmap-{}", std::process::id()));
+        fs::write(&path, "mapped content")?;
+        let file = File::open(&path)?;
+
+        assert_eq!(b"mapped".as_ref(), &*Mmap::new(&file, 6)?);
+        assert_eq!(b"mapped content".as_ref(), &*Mmap::new(&file, 14)?);
+        assert!(Mmap::new(&file, 0).is_err());
+        assert!(Mmap::new(&file, 15).is_err());
+
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
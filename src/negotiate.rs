@@ -0,0 +1,377 @@
+use super::{
+    accept::{self, Item},
+    content_type::{Extension, Mime},
+};
+use std::{
+    cmp::Reverse,
+    ffi::OsStr,
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+};
+
+/// File that may be served for a path, differing from the other variants of
+/// the path by its type or language.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Variant {
+    language: Option<String>,
+    len: u64,
+    mime: Mime,
+    name: String,
+    path: PathBuf,
+}
+
+impl Variant {
+    // Retrieve a reference to the language tag from the name of the file.
+    pub fn language_ref(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    // Retrieve a reference to the path of the file.
+    pub fn path_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Result of negotiating which variant of a path to serve.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// None of the variants are acceptable to the client.
+    NotAcceptable { vary: Option<String> },
+    /// Variant to serve in place of the path.
+    Variant {
+        variant: Variant,
+        vary: Option<String>,
+    },
+}
+
+/// Choose which of the variants of a path to serve using the quality values
+/// of the `Accept` and `Accept-Language` headers of a request.
+///
+/// Variants are the files next to the path named after it with another
+/// extension or a language tag, such as `guide.en.html` and `guide.de.html`
+/// for `guide` or `guide.html`, and `hero.webp` for `hero.png`. A path with
+/// the extension of a known type only has variants of the same kind, so an
+/// image is never served in place of a stylesheet.
+///
+/// Media ranges and language ranges that match a variant more specifically
+/// take precedence, and ties go to the path itself and then to the smallest
+/// file. `None` is returned when the path has no variants other than itself.
+pub fn negotiate(
+    path: &Path,
+    accept: Option<&[u8]>,
+    accept_language: Option<&[u8]>,
+) -> Option<Outcome> {
+    let requested = path.file_name().and_then(OsStr::to_str)?;
+    let mime = extension(requested).map(|(_, extension)| extension.mime());
+
+    // Files without a known type are only negotiated when they don't exist.
+    if mime.is_none() && path.exists() {
+        return None;
+    }
+
+    let variants = variants(path, requested, mime);
+
+    if variants.iter().all(|variant| variant.name == requested) {
+        return None;
+    }
+
+    let vary = vary(&variants, mime);
+    let types = accept.map(accept::parse);
+    let languages = accept_language.map(accept::parse);
+
+    // Candidates are scanned in reverse so that ties go to the first by name.
+    let chosen = variants
+        .into_iter()
+        .rev()
+        .filter_map(|variant| {
+            let (type_quality, type_specificity) = types
+                .as_deref()
+                .map_or((1000, 0), |items| type_quality(items, variant.mime));
+            let (language_quality, language_specificity) =
+                languages.as_deref().map_or((1000, 0), |items| {
+                    language_quality(items, variant.language.as_deref())
+                });
+            let quality = u32::from(type_quality) * u32::from(language_quality);
+            let key = (
+                quality,
+                type_specificity,
+                language_specificity,
+                variant.name == requested,
+                Reverse(variant.len),
+            );
+
+            (quality > 0).then(|| (key, variant))
+        })
+        .max_by_key(|(key, _)| *key);
+
+    Some(match chosen {
+        Some((_, variant)) => Outcome::Variant { variant, vary },
+        None => Outcome::NotAcceptable { vary },
+    })
+}
+
+/// Files next to a path named after it, in order of name.
+fn variants(path: &Path, requested: &str, mime: Option<Mime>) -> Vec<Variant> {
+    let base = extension(requested).map_or(requested, |(base, _)| base);
+    let prefix = format!("{base}.");
+    let entries = path
+        .parent()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten();
+
+    let mut variants = entries
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let (language, extension) = match name.strip_prefix(&prefix)?.split_once('.') {
+                Some((language, extension)) if is_language(language) => {
+                    (Some(language.to_owned()), extension)
+                }
+                Some(_) => return None,
+                None => (None, name.strip_prefix(&prefix)?),
+            };
+            let variant_mime = Extension::new(extension)?.mime();
+
+            if mime.map_or(false, |mime| top_level(mime) != top_level(variant_mime)) {
+                return None;
+            }
+
+            let metadata = fs::metadata(entry.path()).ok().filter(Metadata::is_file)?;
+
+            Some(Variant {
+                language,
+                len: metadata.len(),
+                mime: variant_mime,
+                path: entry.path(),
+                name,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    variants.sort_by(|a, b| a.name.cmp(&b.name));
+
+    variants
+}
+
+/// Headers the choice among variants depends on, for use in `Vary`.
+///
+/// The type matters when any variant has a type other than that of the path,
+/// or the path has no known type, and the language matters when any variant
+/// has one.
+fn vary(variants: &[Variant], mime: Option<Mime>) -> Option<String> {
+    let by_type = variants.iter().any(|variant| Some(variant.mime) != mime);
+    let by_language = variants.iter().any(|variant| variant.language.is_some());
+
+    match (by_type, by_language) {
+        (true, true) => Some("Accept, Accept-Language".to_owned()),
+        (true, false) => Some("Accept".to_owned()),
+        (false, true) => Some("Accept-Language".to_owned()),
+        (false, false) => None,
+    }
+}
+
+/// Quality of a type in an `Accept` header, taken from the most specific
+/// media range matching it, along with how specific that range is.
+fn type_quality(items: &[Item<'_>], mime: Mime) -> (u16, u8) {
+    let name = mime.name();
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let value = item.value();
+            let specificity = if value.eq_ignore_ascii_case(name) {
+                2
+            } else if value
+                .strip_suffix("/*")
+                .map_or(false, |range| range.eq_ignore_ascii_case(top_level(mime)))
+            {
+                1
+            } else if value == "*/*" {
+                0
+            } else {
+                return None;
+            };
+
+            Some((specificity, item.quality()))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or((0, 0), |(specificity, quality)| (quality, specificity))
+}
+
+/// Quality of a language in an `Accept-Language` header, taken from the most
+/// specific language range matching it, along with how specific that range
+/// is.
+///
+/// Ranges match tags they're a prefix of, so `en` matches `en-GB`, and also
+/// tags that are a prefix of them, so `en-GB` matches `en`. Files without a
+/// language are only served when nothing else is acceptable.
+fn language_quality(items: &[Item<'_>], language: Option<&str>) -> (u16, u8) {
+    let language = if let Some(language) = language {
+        language
+    } else {
+        return (1, 0);
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let range = item.value();
+            let specificity = if range.eq_ignore_ascii_case(language) {
+                3
+            } else if is_prefix(range, language) {
+                2
+            } else if is_prefix(language, range) {
+                1
+            } else if range == "*" {
+                0
+            } else {
+                return None;
+            };
+
+            Some((specificity, item.quality()))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or((0, 0), |(specificity, quality)| (quality, specificity))
+}
+
+/// Split a file name into its base and known extension.
+fn extension(name: &str) -> Option<(&str, Extension)> {
+    let (base, extension) = name.rsplit_once('.')?;
+
+    Some((base, Extension::new(extension)?))
+}
+
+/// Whether part of a file name is a language tag, such as `en` or `pt-BR`,
+/// with a primary language of two letters.
+fn is_language(input: &str) -> bool {
+    let mut subtags = input.split('-');
+    let primary = subtags.next().unwrap_or_default();
+
+    primary.len() == 2
+        && primary.bytes().all(|x| x.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.bytes().all(|x| x.is_ascii_alphanumeric())
+        })
+}
+
+/// Whether a language tag is a prefix of another at a subtag boundary.
+fn is_prefix(prefix: &str, tag: &str) -> bool {
+    tag.len() > prefix.len()
+        && tag.as_bytes()[prefix.len()] == b'-'
+        && tag[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// Top-level type of a MIME type, such as `image` for `image/png`.
+fn top_level(mime: Mime) -> &'static str {
+    mime.name().split('/').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_language, language_quality, negotiate, type_quality, Outcome};
+    use crate::{accept, content_type::Mime};
+    use std::{env, error::Error, fs, path::Path};
+
+    #[test]
+    fn test_type_quality() {
+        let items = accept::parse(b"image/avif,image/webp,image/*;q=0.8,*/*;q=0.5");
+        assert_eq!((1000, 2), type_quality(&items, Mime::ImageAvif));
+        assert_eq!((800, 1), type_quality(&items, Mime::ImagePng));
+        assert_eq!((500, 0), type_quality(&items, Mime::TextHtml));
+        assert_eq!((0, 0), type_quality(&[], Mime::TextHtml));
+    }
+
+    #[test]
+    fn test_language_quality() {
+        let items = accept::parse(b"de-CH, en;q=0.7, *;q=0.1");
+        assert_eq!((1000, 1), language_quality(&items, Some("de")));
+        assert_eq!((700, 2), language_quality(&items, Some("en-GB")));
+        assert_eq!((100, 0), language_quality(&items, Some("fr")));
+        assert_eq!((1, 0), language_quality(&items, None));
+        assert_eq!((0, 0), language_quality(&items[..2], Some("fr")));
+    }
+
+    #[test]
+    fn test_is_language() {
+        assert!(is_language("en"));
+        assert!(is_language("pt-BR"));
+        assert!(!is_language("min"));
+        assert!(!is_language("3f"));
+        assert!(!is_language(""));
+    }
+
+    #[test]
+    fn test_negotiate() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-negotiate-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+
+        for (name, content) in [
+            ("guide.en.html", "guide"),
+            ("guide.de.html", "Anleitung"),
+            ("hero.png", "png image"),
+            ("hero.webp", "webp"),
+            ("hero.avif", "avif"),
+            ("hero.txt", "text"),
+            ("app.js", "script"),
+            ("app.min.js", "min"),
+        ] {
+            fs::write(dir.join(name), content)?;
+        }
+
+        let chosen = |path: &str, accept: &[u8], language: &[u8]| match negotiate(
+            &dir.join(path),
+            Some(accept),
+            Some(language),
+        ) {
+            Some(Outcome::Variant { variant, vary }) => Some((
+                variant.path_ref().file_name().unwrap().to_owned(),
+                variant.language_ref().map(str::to_owned),
+                vary,
+            )),
+            Some(Outcome::NotAcceptable { .. }) => None,
+            None => panic!("{path} has no variants"),
+        };
+        let name = |x: &str| Path::new(x).as_os_str().to_owned();
+        assert_eq!(
+            Some((
+                name("guide.de.html"),
+                Some("de".to_owned()),
+                Some("Accept, Accept-Language".to_owned())
+            )),
+            chosen("guide", b"text/html", b"de-DE,en;q=0.5")
+        );
+        assert_eq!(
+            Some((
+                name("guide.en.html"),
+                Some("en".to_owned()),
+                Some("Accept-Language".to_owned())
+            )),
+            chosen("guide.html", b"*/*", b"fr, en;q=0.1")
+        );
+        assert_eq!(None, chosen("guide", b"text/html", b"fr"));
+        assert_eq!(None, chosen("guide", b"image/png", b"en"));
+
+        let vary = Some("Accept".to_owned());
+        assert_eq!(
+            Some((name("hero.avif"), None, vary.clone())),
+            chosen("hero.png", b"image/avif,image/webp,*/*", b"en")
+        );
+        assert_eq!(
+            Some((name("hero.webp"), None, vary.clone())),
+            chosen("hero.png", b"image/webp,image/*", b"")
+        );
+        assert_eq!(
+            Some((name("hero.png"), None, vary)),
+            chosen("hero.png", b"*/*", b"")
+        );
+
+        // Files of other kinds and parts of names that aren't languages
+        // aren't variants.
+        assert!(negotiate(&dir.join("app.js"), None, None).is_none());
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use std::borrow::Cow;
+
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Percent-encode a string for use in a path, leaving unreserved characters,
+/// slashes, and sub-delimiters that are safe within HTML attributes as-is.
+pub fn encode_path(input: &str) -> String {
+    encode(input, |byte| {
+        is_unreserved(byte)
+            || matches!(byte, b'/' | b'!' | b'$' | b'(' | b')' | b'*' | b'+')
+            || matches!(byte, b',' | b';' | b'=' | b':' | b'@')
+    })
+}
+
+/// Percent-encode a string as the value of an extended header parameter,
+/// such as `filename*`, leaving only attribute characters as-is.
+///
+/// Refer to [RFC 8187 § 3.2.1] for the characters allowed.
+///
+/// [RFC 8187 § 3.2.1]: https://www.rfc-editor.org/rfc/rfc8187#section-3.2.1
+pub fn encode_attr(input: &str) -> String {
+    encode(input, |byte| {
+        byte.is_ascii_alphanumeric()
+            || matches!(
+                byte,
+                b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+            )
+    })
+}
+
+/// Decode percent-encoded bytes, leaving invalid escapes as-is.
+///
+/// Input without escapes is borrowed.
+pub fn decode(input: &[u8]) -> Cow<'_, [u8]> {
+    if !input.contains(&b'%') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut output = Vec::with_capacity(input.len());
+    let mut rest = input;
+
+    while let [byte, tail @ ..] = rest {
+        if let (b'%', [high, low, tail @ ..]) = (byte, tail) {
+            if let (Some(high), Some(low)) = (hex(*high), hex(*low)) {
+                output.push(high << 4 | low);
+                rest = tail;
+
+                continue;
+            }
+        }
+
+        output.push(*byte);
+        rest = tail;
+    }
+
+    Cow::Owned(output)
+}
+
+fn encode(input: &str, keep: impl Fn(u8) -> bool) -> String {
+    let mut output = String::with_capacity(input.len());
+
+    for byte in input.bytes() {
+        if keep(byte) {
+            output.push(char::from(byte));
+        } else {
+            output.push('%');
+            output.push(char::from(HEX[usize::from(byte >> 4)]));
+            output.push(char::from(HEX[usize::from(byte & 0xf)]));
+        }
+    }
+
+    output
+}
+
+/// Value of a hexadecimal digit.
+const fn hex(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+const fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode_attr, encode_path};
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!("docs/a%20b.txt", encode_path("docs/a b.txt"));
+        assert_eq!("%27%3F%23%25", encode_path("'?#%"));
+        assert_eq!("r%C3%A9sum%C3%A9.pdf", encode_path("résumé.pdf"));
+        assert_eq!("a%20b%2Fc.txt", encode_attr("a b/c.txt"));
+        assert_eq!("%E2%82%AC%20rates.txt", encode_attr("€ rates.txt"));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(Cow::Borrowed(b"a.txt"), decode(b"a.txt"));
+        assert_eq!(
+            "résumé.pdf".as_bytes(),
+            decode(b"r%C3%A9sum%c3%a9.pdf").as_ref()
+        );
+        assert_eq!(b"100%".as_ref(), decode(b"100%").as_ref());
+        assert_eq!(b"%zz%2".as_ref(), decode(b"%zz%2").as_ref());
+    }
+}
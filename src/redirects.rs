@@ -0,0 +1,395 @@
+use super::response::Status;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use std::{collections::HashMap, error::Error};
+
+/// Name of the file of rules in the served directory.
+pub const FILE_NAME: &str = "_redirects";
+
+/// Statuses a rule may have.
+const STATUSES: &[u16] = &[200, 301, 302, 307, 308, 404];
+
+#[derive(Debug)]
+pub enum RuleError {
+    /// The rule has a path to match but no target.
+    MissingTarget,
+    /// A path to match doesn't start with a `/`.
+    PathInvalid { path: String },
+    /// A rewrite targets another server, which would need a proxy.
+    ProxyUnsupported { target: String },
+    /// A splat isn't the last segment of a path to match.
+    SplatNotLast,
+    /// The status isn't one of those supported.
+    StatusInvalid { status: String },
+    /// There are more parts after the status.
+    TrailingInput { input: String },
+}
+
+impl Display for RuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::MissingTarget => f.write_str("rule has no target"),
+            Self::PathInvalid { path } => {
+                f.write_str("path '")?;
+                f.write_str(path)?;
+                f.write_str("' does not start with '/'")
+            }
+            Self::ProxyUnsupported { target } => {
+                f.write_str("rewriting to '")?;
+                f.write_str(target)?;
+                f.write_str("' would need a proxy, which is not supported")
+            }
+            Self::SplatNotLast => f.write_str("splat is not the last segment of the path"),
+            Self::StatusInvalid { status } => {
+                f.write_str("status '")?;
+                f.write_str(status)?;
+                f.write_str("' is not one of 200, 301, 302, 307, 308, or 404")
+            }
+            Self::TrailingInput { input } => {
+                f.write_str("unexpected '")?;
+                f.write_str(input)?;
+                f.write_str("' after the status")
+            }
+        }
+    }
+}
+
+impl Error for RuleError {}
+
+/// Segment of a path to match.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    /// Segment matched exactly.
+    Literal(String),
+    /// Segment matching anything, such as `:slug`, captured with its name.
+    Placeholder(String),
+}
+
+/// Value of a query parameter to match.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Condition {
+    /// Parameter with an exact value.
+    Literal(String),
+    /// Parameter with any value, captured with a name.
+    Placeholder(String),
+}
+
+/// Rule redirecting or rewriting requests for paths matching a pattern, in
+/// the form `FROM [PARAM=VALUE...] TO [STATUS][!]`, such as
+/// `/blog/:slug /posts/:slug 301`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rule {
+    conditions: Vec<(String, Condition)>,
+    force: bool,
+    from: Vec<Segment>,
+    splat: bool,
+    status: Status,
+    to: String,
+}
+
+impl Rule {
+    /// Parse a rule, defaulting to a `301 Moved Permanently` redirect.
+    ///
+    /// Rules ending with a `*` capture the rest of the path as a splat, and
+    /// rules with a `!` after the status apply even to paths with a file.
+    pub fn new(rule: &str) -> Result<Self, RuleError> {
+        let mut parts = rule.split_whitespace();
+        let from = parts.next().ok_or(RuleError::MissingTarget)?;
+
+        if !from.starts_with('/') {
+            return Err(RuleError::PathInvalid {
+                path: from.to_owned(),
+            });
+        }
+
+        let mut conditions = Vec::new();
+        let to = loop {
+            let part = parts.next().ok_or(RuleError::MissingTarget)?;
+
+            match part.split_once('=') {
+                Some((name, value)) if !part.starts_with('/') && !part.contains("://") => {
+                    let condition = value.strip_prefix(':').map_or_else(
+                        || Condition::Literal(value.to_owned()),
+                        |name| Condition::Placeholder(name.to_owned()),
+                    );
+                    conditions.push((name.to_owned(), condition));
+                }
+                _ => break part.to_owned(),
+            }
+        };
+
+        let (status, force) = match parts.next() {
+            Some(status) => {
+                let (code, force) = status
+                    .strip_suffix('!')
+                    .map_or((status, false), |code| (code, true));
+                let code = code
+                    .parse()
+                    .ok()
+                    .filter(|code| STATUSES.contains(code))
+                    .ok_or_else(|| RuleError::StatusInvalid {
+                        status: status.to_owned(),
+                    })?;
+
+                (Status::new(code), force)
+            }
+            None => (Status::MOVED_PERMANENTLY, false),
+        };
+
+        if let Some(input) = parts.next() {
+            return Err(RuleError::TrailingInput {
+                input: input.to_owned(),
+            });
+        }
+
+        if !status.is_redirection() && !to.starts_with('/') {
+            return Err(RuleError::ProxyUnsupported { target: to });
+        }
+
+        let mut segments = segments(from).collect::<Vec<_>>();
+        let splat = segments.last() == Some(&"*");
+
+        if splat {
+            let _ = segments.pop();
+        }
+
+        if segments.contains(&"*") {
+            return Err(RuleError::SplatNotLast);
+        }
+
+        let from = segments
+            .into_iter()
+            .map(|segment| {
+                segment.strip_prefix(':').map_or_else(
+                    || Segment::Literal(segment.to_owned()),
+                    |name| Segment::Placeholder(name.to_owned()),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            conditions,
+            force,
+            from,
+            splat,
+            status,
+            to,
+        })
+    }
+
+    /// Target of the rule for a path and query, with the placeholders
+    /// captured from them substituted.
+    fn target(&self, path: &str, query: &[(&str, &str)]) -> Option<String> {
+        let mut captures = HashMap::new();
+        let mut path = segments(path);
+
+        for segment in &self.from {
+            let value = path.next()?;
+
+            match segment {
+                Segment::Literal(literal) if literal == value => {}
+                Segment::Literal(_) => return None,
+                Segment::Placeholder(name) => {
+                    let _ = captures.insert(name.as_str(), value.to_owned());
+                }
+            }
+        }
+
+        let rest = path.collect::<Vec<_>>();
+
+        if self.splat {
+            let _ = captures.insert("splat", rest.join("/"));
+        } else if !rest.is_empty() {
+            return None;
+        }
+
+        for (name, condition) in &self.conditions {
+            let value = query
+                .iter()
+                .find(|(param, _)| param == name)
+                .map(|(_, value)| *value)?;
+
+            match condition {
+                Condition::Literal(literal) if literal == value => {}
+                Condition::Literal(_) => return None,
+                Condition::Placeholder(name) => {
+                    let _ = captures.insert(name.as_str(), value.to_owned());
+                }
+            }
+        }
+
+        Some(substitute(&self.to, &captures))
+    }
+}
+
+/// Rule that matched a request.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Match {
+    /// Status of the rule.
+    pub status: Status,
+    /// Target of the rule with its placeholders substituted.
+    pub target: String,
+}
+
+/// Parse the rules of a `_redirects` file, skipping blank lines and comments
+/// starting with `#`.
+///
+/// Errors come with the number of the line of the rule.
+pub fn parse(input: &str) -> Result<Vec<Rule>, (usize, RuleError)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| Rule::new(line).map_err(|source| (number, source)))
+        .collect()
+}
+
+/// Find the first rule matching the path and query of a request.
+///
+/// Rules that aren't forced don't apply to paths with a file, which shadows
+/// them.
+pub fn find(rules: &[Rule], path: &str, query: Option<&[u8]>, exists: bool) -> Option<Match> {
+    let query = query.map(String::from_utf8_lossy).unwrap_or_default();
+    let params = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| param.split_once('=').unwrap_or((param, "")))
+        .collect::<Vec<_>>();
+
+    rules
+        .iter()
+        .filter(|rule| rule.force || !exists)
+        .find_map(|rule| {
+            rule.target(path, &params).map(|target| Match {
+                status: rule.status,
+                target,
+            })
+        })
+}
+
+/// Non-empty segments of a path, ignoring leading and trailing slashes.
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// Replace the `:name` placeholders of a target with captured values,
+/// leaving unknown names such as ports as-is.
+fn substitute(target: &str, captures: &HashMap<&str, String>) -> String {
+    let mut output = String::with_capacity(target.len());
+    let mut rest = target;
+
+    while let Some(start) = rest.find(':') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let len = after
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(after.len());
+
+        match captures.get(&after[..len]) {
+            Some(value) if len > 0 => output.push_str(value),
+            _ => {
+                output.push(':');
+                output.push_str(&after[..len]);
+            }
+        }
+
+        rest = &after[len..];
+    }
+
+    output.push_str(rest);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, parse, Match, Rule, RuleError};
+    use crate::response::Status;
+
+    #[test]
+    fn test_rule() {
+        assert!(Rule::new("/a /b").is_ok());
+        assert!(Rule::new("/a https://example.com/:splat 302").is_ok());
+        assert!(matches!(Rule::new("/a"), Err(RuleError::MissingTarget)));
+        assert!(matches!(
+            Rule::new("a /b"),
+            Err(RuleError::PathInvalid { .. })
+        ));
+        assert!(matches!(
+            Rule::new("/a https://example.com 200"),
+            Err(RuleError::ProxyUnsupported { .. })
+        ));
+        assert!(matches!(
+            Rule::new("/a/*/b /c"),
+            Err(RuleError::SplatNotLast)
+        ));
+        assert!(matches!(
+            Rule::new("/a /b 303"),
+            Err(RuleError::StatusInvalid { .. })
+        ));
+        assert!(matches!(
+            Rule::new("/a /b 301 x"),
+            Err(RuleError::TrailingInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find() {
+        let rules = parse(
+            "# Moved sections
+            /news/*             /blog/:splat                301
+            /blog/:year/:slug   /posts/:year-:slug          308
+            /store id=:id       /products/:id               302
+            /lang lang=fr       /fr/                        307
+            /old                /new                        301!
+            /app/*              /app/index.html             200
+            /*                  /404.html                   404
+            ",
+        )
+        .unwrap();
+        let target = |path, query: Option<&[u8]>, exists| {
+            find(&rules, path, query, exists)
+                .map(|Match { status, target }| (status.code(), target))
+        };
+
+        assert_eq!(
+            Some((301, "/blog/2024/launch".to_owned())),
+            target("/news/2024/launch", None, false)
+        );
+        assert_eq!(
+            Some((301, "/blog/".to_owned())),
+            target("/news", None, false)
+        );
+        assert_eq!(
+            Some((308, "/posts/2024-launch".to_owned())),
+            target("/blog/2024/launch/", None, false)
+        );
+        assert_eq!(
+            Some((302, "/products/7".to_owned())),
+            target("/store", Some(b"ref=x&id=7"), false)
+        );
+        assert_eq!(
+            Some((307, "/fr/".to_owned())),
+            target("/lang", Some(b"lang=fr"), false)
+        );
+        assert_eq!(
+            Some((404, "/404.html".to_owned())),
+            target("/lang", Some(b"lang=de"), false)
+        );
+        assert_eq!(Some((301, "/new".to_owned())), target("/old", None, true));
+        assert_eq!(
+            Some((200, "/app/index.html".to_owned())),
+            target("/app/settings", None, false)
+        );
+        assert_eq!(None, target("/app/main.js", None, true));
+        assert_eq!(Status::NOT_FOUND, rules[6].status);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(matches!(
+            parse("/a /b\n\n/c /d 999"),
+            Err((3, RuleError::StatusInvalid { .. }))
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use super::{
+    super::{date, etag::ETag},
+    Request,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Result of evaluating the preconditions of a request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The representation hasn't changed and the content may be omitted.
+    NotModified,
+    /// A precondition for the request doesn't hold.
+    PreconditionFailed,
+    /// Respond as usual.
+    Proceed,
+}
+
+/// Evaluate the conditional headers of a GET or HEAD request against the
+/// current entity tag and modification time of a representation.
+///
+/// Date validators are only considered when the corresponding entity tag
+/// header is absent, as per RFC 9110 section 13.2.2.
+pub fn evaluate(
+    request: &Request<'_>,
+    etag: Option<&ETag>,
+    modified: Option<SystemTime>,
+) -> Outcome {
+    // HTTP dates have a resolution of seconds.
+    let modified = modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs());
+    let since = |name| {
+        request
+            .header(name)
+            .and_then(date::parse)
+            .and_then(|date| date.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+    };
+
+    if let Some(list) = request.header("If-Match") {
+        let matches = etag.map_or_else(|| list == b"*", |etag| etag.matches_any(list, false));
+
+        if !matches {
+            return Outcome::PreconditionFailed;
+        }
+    } else if let (Some(modified), Some(since)) = (modified, since("If-Unmodified-Since")) {
+        if modified > since {
+            return Outcome::PreconditionFailed;
+        }
+    }
+
+    if let Some(list) = request.header("If-None-Match") {
+        let matches = etag.map_or_else(|| list == b"*", |etag| etag.matches_any(list, true));
+
+        if matches {
+            return Outcome::NotModified;
+        }
+    } else if let (Some(modified), Some(since)) = (modified, since("If-Modified-Since")) {
+        if modified <= since {
+            return Outcome::NotModified;
+        }
+    }
+
+    Outcome::Proceed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, Outcome};
+    use crate::{etag::ETag, request::Request};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const MODIFIED: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+
+    fn modified() -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(784_111_777_500)
+    }
+
+    fn outcome(headers: &str, etag: Option<&ETag>) -> Outcome {
+        let input = format!("GET / HTTP/1.1\r\n{headers}\r\n");
+        let request = Request::from_input(input.as_bytes()).unwrap();
+
+        evaluate(&request, etag, Some(modified()))
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let etag = ETag::from_content(b"test");
+        let tag = etag.to_string();
+
+        assert_eq!(Outcome::Proceed, outcome("", Some(&etag)));
+        assert_eq!(
+            Outcome::NotModified,
+            outcome(&format!("If-None-Match: {tag}\r\n"), Some(&etag))
+        );
+        assert_eq!(
+            Outcome::NotModified,
+            outcome(&format!("if-none-match: \"a\", W/{tag}\r\n"), Some(&etag))
+        );
+        assert_eq!(
+            Outcome::Proceed,
+            outcome("If-None-Match: \"a\"\r\n", Some(&etag))
+        );
+        assert_eq!(Outcome::NotModified, outcome("If-None-Match: *\r\n", None));
+        assert_eq!(
+            Outcome::PreconditionFailed,
+            outcome("If-Match: \"a\"\r\n", Some(&etag))
+        );
+        assert_eq!(
+            Outcome::PreconditionFailed,
+            outcome(&format!("If-Match: W/{tag}\r\n"), Some(&etag))
+        );
+        assert_eq!(
+            Outcome::Proceed,
+            outcome(&format!("If-Match: {tag}\r\n"), Some(&etag))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_dates() {
+        let etag = ETag::from_content(b"test");
+
+        assert_eq!(
+            Outcome::NotModified,
+            outcome(&format!("If-Modified-Since: {MODIFIED}\r\n"), None)
+        );
+        assert_eq!(
+            Outcome::Proceed,
+            outcome("If-Modified-Since: Sun, 06 Nov 1994 08:49:36 GMT\r\n", None)
+        );
+        assert_eq!(
+            Outcome::Proceed,
+            outcome("If-Modified-Since: invalid\r\n", None)
+        );
+        assert_eq!(
+            Outcome::PreconditionFailed,
+            outcome(
+                "If-Unmodified-Since: Sat, 05 Nov 1994 08:49:37 GMT\r\n",
+                None
+            )
+        );
+        assert_eq!(
+            Outcome::Proceed,
+            outcome(&format!("If-Unmodified-Since: {MODIFIED}\r\n"), None)
+        );
+
+        // Entity tag validators take precedence over dates.
+        assert_eq!(
+            Outcome::Proceed,
+            outcome(
+                &format!("If-None-Match: \"a\"\r\nIf-Modified-Since: {MODIFIED}\r\n"),
+                Some(&etag)
+            )
+        );
+        assert_eq!(
+            Outcome::Proceed,
+            outcome(
+                &format!(
+                    "If-Match: {etag}\r\nIf-Unmodified-Since: Sat, 05 Nov 1994 08:49:37 GMT\r\n"
+                ),
+                Some(&etag)
+            )
+        );
+    }
+}
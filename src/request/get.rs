@@ -1,6 +1,31 @@
-use super::super::response::{Response, WriteError};
+use super::{
+    super::{
+        cache_control,
+        charset::Charset,
+        compress::Encoding,
+        content_type::{Extension, Mime},
+        date, download,
+        env::Environment,
+        etag::{ETag, Mode as ETagMode},
+        hints::Hint,
+        response::{Body, Response, Status, WriteError},
+        state::State,
+    },
+    conditional::{self, Outcome},
+    Request, Version,
+};
 use core::fmt::{Display, Formatter, Result as FmtResult};
-use std::{error::Error, ffi::OsStr, fs, io::ErrorKind, net::TcpStream, path::Path};
+use std::{
+    borrow::Cow,
+    error::Error,
+    ffi::OsStr,
+    fs::{self, File, Metadata},
+    io::{Error as IoError, ErrorKind, Read, Seek, SeekFrom},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
 
 /// Error occurred when processing a GET request.
 #[derive(Debug)]
@@ -18,28 +43,709 @@ impl Display for GetError {
     }
 }
 
-impl Error for GetError {}
+impl Error for GetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Write { source } => Some(source),
+        }
+    }
+}
+
+/// Number of bytes read from the start of a file to sniff its charset.
+const PREFIX_LEN: u64 = 8192;
+
+/// Metadata of the variant of a file being served.
+struct Variant<'a> {
+    attachment: Option<&'a str>,
+    cache_control: Option<&'a str>,
+    charset: Option<Charset>,
+    encoding: Option<Encoding>,
+    etag: Option<ETag>,
+    mime: Mime,
+    modified: Option<SystemTime>,
+    precompressed: bool,
+    vary: &'static [&'static str],
+}
+
+impl<'a> Variant<'a> {
+    /// Create an unencoded variant of a file.
+    const fn new(
+        mime: Mime,
+        etag: Option<ETag>,
+        modified: Option<SystemTime>,
+        cache_control: Option<&'a str>,
+    ) -> Self {
+        Self {
+            attachment: None,
+            cache_control,
+            charset: None,
+            encoding: None,
+            etag,
+            mime,
+            modified,
+            precompressed: false,
+            vary: &[],
+        }
+    }
+
+    /// Compress the variant on the fly if it's worth it and the request
+    /// accepts an encoding, with the entity tag of the unencoded file
+    /// adjusted for the encoding.
+    fn compress(mut self, request: &Request<'_>, env: &Environment, len: u64) -> Self {
+        let compressible =
+            env.compress() && self.mime.is_compressible() && len >= env.compress_min_size();
+        self.encoding = request
+            .header("Accept-Encoding")
+            .filter(|_| compressible)
+            .and_then(|accept| Encoding::negotiate(accept, Encoding::COMPRESSIBLE));
+        self.etag = self.etag.map(|etag| etag.for_encoding(self.encoding));
+
+        self.vary_encoding(compressible)
+    }
+
+    /// Mark the variant as read from a sidecar file already compressed with
+    /// an encoding.
+    const fn precompressed(mut self, encoding: Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self.precompressed = true;
+
+        self.vary_encoding(true)
+    }
+
+    /// Describe the content of the variant as text in a charset.
+    const fn charset(mut self, charset: Option<Charset>) -> Self {
+        self.charset = charset;
+
+        self
+    }
+
+    /// Send the variant as an attachment to be downloaded with a file name.
+    const fn attachment(mut self, file_name: Option<&'a str>) -> Self {
+        self.attachment = file_name;
+
+        self
+    }
+
+    /// Mark the variant as chosen by the `Accept-Encoding` header if it is.
+    const fn vary_encoding(mut self, vary: bool) -> Self {
+        if vary {
+            self.vary = &["Accept-Encoding"];
+        }
+
+        self
+    }
+
+    /// Response with the validators and caching policy of the variant.
+    fn response<'b>(&self, status: Status) -> Response<'b> {
+        let mut response = Response::new(status);
+        let headers = response.headers_mut();
+
+        if let Some(cache_control) = self.cache_control {
+            headers.append("Cache-Control", cache_control);
+        }
+
+        if let Some(etag) = &self.etag {
+            headers.append("ETag", etag.to_string());
+        }
+
+        if let Some(modified) = self.modified {
+            headers.append("Last-Modified", date::format(modified));
+        }
+
+        for name in self.vary {
+            headers.append("Vary", *name);
+        }
+
+        response
+    }
+
+    /// Response with the content of the variant, described by its type and
+    /// encoding.
+    fn ok<'b>(&self, body: Body<'b>, hints: &[Hint]) -> Response<'b> {
+        let mut response = self.response(Status::OK);
+        let headers = response.headers_mut();
+
+        for hint in hints {
+            headers.append("Link", hint.to_string());
+        }
+
+        headers.append(
+            "Content-Type",
+            self.mime.with_charset(self.charset).to_string(),
+        );
+
+        if let Some(encoding) = self.encoding {
+            headers.append("Content-Encoding", encoding.name());
+        }
+
+        if let Some(file_name) = self.attachment {
+            headers.append(
+                "Content-Disposition",
+                download::content_disposition(file_name),
+            );
+        }
+
+        response.body(body)
+    }
+}
 
 /// Handle a GET request.
-pub fn get(stream: &mut TcpStream, path: &Path) -> Result<(), GetError> {
-    match fs::read(path) {
-        Ok(bytes) => Response::new(&bytes)
-            .extension(path.extension().and_then(OsStr::to_str))
-            .ok()
-            .write(stream)
-            .map_err(|source| GetError::Write { source }),
-        Err(source) => {
-            let response = Response::new(b"");
-
-            let response = match source.kind() {
-                ErrorKind::Other if source.raw_os_error() == Some(21) => response.forbidden(),
-                ErrorKind::NotFound => response.not_found(),
-                _ => response.internal_service_error(),
-            };
-
-            response
-                .write(stream)
-                .map_err(|source| GetError::Write { source })
+pub fn get(
+    stream: &mut TcpStream,
+    request: &Request<'_>,
+    path: &Path,
+    env: &Environment,
+    state: &mut State,
+) -> Result<Response<'static>, GetError> {
+    if let Some(response) = cached(stream, request, path, env, state)? {
+        return Ok(response);
+    }
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(source) => return Ok(error(&source)),
+    };
+    let mut metadata = file.metadata().ok();
+    let plain_modified = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.modified().ok());
+    let relative_path = relative_path(request, path, env);
+    let cache_control = cache_control::resolve(env.cache_rules_ref(), &request.relative_path());
+    let attachment = attachment(request, path, &relative_path, env);
+
+    let extension = extension(path);
+    let is_html = matches!(extension, Some(Extension::Html));
+
+    // The charset of text files is sniffed from the plain file, since it may
+    // be served from a compressed sidecar.
+    let plain_charset = match plain_charset(&mut file, extension, env) {
+        Ok(charset) => charset,
+        Err(source) => return Ok(error(&source)),
+    };
+
+    let (has_sidecars, sidecar) = sidecar(request, path, extension);
+    let precompressed = sidecar.map(|(encoding, sidecar)| {
+        metadata = sidecar.metadata().ok();
+        file = sidecar;
+
+        encoding
+    });
+
+    // Validators are those of the file actually being read.
+    let len = metadata.as_ref().map_or(0, Metadata::len);
+    let modified = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.modified().ok());
+    let metadata_etag = metadata
+        .as_ref()
+        .and_then(|metadata| ETag::from_metadata(metadata, env.etag()));
+    let variant = |mime, charset, len, etag| {
+        let variant = Variant::new(mime, etag, modified, cache_control)
+            .attachment(attachment)
+            .charset(charset)
+            .vary_encoding(has_sidecars);
+
+        match precompressed {
+            Some(encoding) => variant.precompressed(encoding),
+            None => variant.compress(request, env, len),
+        }
+    };
+
+    // Conditional requests can be answered without reading the file when the
+    // entity tag doesn't depend on the content and the type of the file, and
+    // so the variant being served, is known from its extension.
+    let known = extension
+        .filter(|_| env.etag() != ETagMode::Hash)
+        .map(|extension| variant(extension.mime(), plain_charset, len, metadata_etag.clone()));
+
+    if let Some(variant) = &known {
+        if let Some(response) = precondition(request, variant) {
+            return Ok(response);
+        }
+    }
+
+    if is_html {
+        early_hints(stream, request, env, state, path, plain_modified)?;
+    }
+
+    // The file only needs to be read up front when its content is scanned,
+    // compressed, or cached, otherwise it's copied to the stream as it's read.
+    let cacheable = !has_sidecars && state.file_cache_mut().accepts(len);
+
+    if let Some(variant) = known.as_ref().filter(|variant| {
+        !is_html && !cacheable && (variant.encoding.is_none() || variant.precompressed)
+    }) {
+        return Ok(variant.ok(Body::file(file, len), &[]));
+    }
+
+    let mut bytes = Vec::new();
+
+    if let Err(source) = file.read_to_end(&mut bytes) {
+        return Ok(error(&source));
+    }
+
+    let mime = match extension {
+        Some(extension) => extension.mime(),
+        None if bytes.is_empty() => Mime::OctetStream,
+        None => Mime::from_input(&bytes),
+    };
+    let etag = if env.etag() == ETagMode::Hash {
+        Some(ETag::from_content(&bytes))
+    } else {
+        metadata_etag
+    };
+    // Compressed content can't be sniffed, unlike the plain file.
+    let charset = precompressed.map_or_else(
+        || Charset::detect(mime, &bytes, false, env.default_charset()),
+        |_| plain_charset,
+    );
+    let variant = variant(mime, charset, bytes.len() as u64, etag.clone());
+
+    if known.is_none() {
+        if let Some(response) = precondition(request, &variant) {
+            return Ok(response);
+        }
+    }
+
+    let cached = metadata
+        .as_ref()
+        .filter(|_| cacheable)
+        .and_then(|metadata| cache(state, path, metadata, &bytes, &variant, etag));
+    let hints = if is_html {
+        page_hints(state, path, plain_modified, variant.precompressed, &bytes)
+    } else {
+        Cow::default()
+    };
+    let encoding = variant.encoding.filter(|_| !variant.precompressed);
+    let body = cached.map_or_else(|| Body::Bytes(encode(bytes, encoding).into()), Body::Shared);
+
+    Ok(variant.ok(body, &hints))
+}
+
+/// Respond with the content of a file from the in-memory cache if it's
+/// cached and hasn't changed since, without reading it again.
+fn cached(
+    stream: &mut TcpStream,
+    request: &Request<'_>,
+    path: &Path,
+    env: &Environment,
+    state: &mut State,
+) -> Result<Option<Response<'static>>, GetError> {
+    // Files aren't looked up at all while the cache is disabled.
+    if !state.file_cache_mut().is_enabled() {
+        return Ok(None);
+    }
+
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(None),
+    };
+    let (mime, charset, etag, modified) = match state.file_cache_mut().get(path, &metadata) {
+        Some(entry) => (
+            entry.mime(),
+            entry.charset(),
+            entry.etag_ref().cloned(),
+            entry.modified(),
+        ),
+        None => return Ok(None),
+    };
+
+    // Files are only cached without sidecars, which may have been added since.
+    if !sidecars(path).is_empty() {
+        return Ok(None);
+    }
+
+    let relative_path = relative_path(request, path, env);
+    let cache_control = cache_control::resolve(env.cache_rules_ref(), &request.relative_path());
+    let variant = Variant::new(mime, etag, Some(modified), cache_control)
+        .attachment(attachment(request, path, &relative_path, env))
+        .charset(charset)
+        .compress(request, env, metadata.len());
+
+    if let Some(response) = precondition(request, &variant) {
+        return Ok(Some(response));
+    }
+
+    let content = match state.file_cache_mut().content(path, variant.encoding) {
+        Some(content) => content,
+        None => return Ok(None),
+    };
+    let hints = if matches!(extension(path), Some(Extension::Html)) {
+        early_hints(stream, request, env, state, path, Some(modified))?;
+
+        page_hints(state, path, Some(modified), true, &[])
+    } else {
+        Cow::default()
+    };
+
+    Ok(Some(variant.ok(Body::Shared(content), &hints)))
+}
+
+/// Cache the plain content of a file along with the details of its variant,
+/// returning the content with the encoding of the variant applied.
+fn cache(
+    state: &mut State,
+    path: &Path,
+    metadata: &Metadata,
+    bytes: &[u8],
+    variant: &Variant<'_>,
+    etag: Option<ETag>,
+) -> Option<Rc<[u8]>> {
+    let cache = state.file_cache_mut();
+    cache.insert(path, metadata, bytes, variant.mime, variant.charset, etag);
+
+    cache.content(path, variant.encoding)
+}
+
+/// Content with an encoding applied, or as it is if it can't be encoded.
+fn encode(bytes: Vec<u8>, encoding: Option<Encoding>) -> Vec<u8> {
+    encoding
+        .and_then(|encoding| encoding.encode(&bytes))
+        .unwrap_or(bytes)
+}
+
+/// Extension of a file if it's of a known type.
+fn extension(path: &Path) -> Option<Extension> {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .and_then(Extension::new)
+}
+
+/// Preload hints of an HTML page, scanned from its content unless it's
+/// compressed.
+fn page_hints<'a>(
+    state: &'a mut State,
+    path: &Path,
+    modified: Option<SystemTime>,
+    precompressed: bool,
+    bytes: &[u8],
+) -> Cow<'a, [Hint]> {
+    if precompressed {
+        // Compressed pages can't be scanned, but the plain page may have been.
+        modified
+            .and_then(|modified| state.hints_mut().get(path, modified))
+            .map_or_else(Cow::default, Cow::Borrowed)
+    } else {
+        state.hints_mut().hints(path, modified, bytes)
+    }
+}
+
+/// Path of a file relative to the served directory, which may be an index
+/// file rather than the request target.
+fn relative_path<'a>(request: &Request<'a>, path: &'a Path, env: &Environment) -> Cow<'a, str> {
+    path.strip_prefix(env.dir_ref())
+        .map_or_else(|_| request.relative_path(), |path| path.to_string_lossy())
+}
+
+/// File name to send a file as an attachment with, if it's to be downloaded
+/// because of the request or its path.
+fn attachment<'a>(
+    request: &Request<'_>,
+    path: &'a Path,
+    relative_path: &str,
+    env: &Environment,
+) -> Option<&'a str> {
+    let download = download::requested(request.query())
+        || env
+            .download_globs_ref()
+            .iter()
+            .any(|glob| glob.matches(relative_path));
+
+    path.file_name()
+        .and_then(OsStr::to_str)
+        .filter(|_| download)
+}
+
+/// Sniff the charset of a text file of a known type from its start,
+/// rewinding it afterwards.
+fn plain_charset(
+    file: &mut File,
+    extension: Option<Extension>,
+    env: &Environment,
+) -> Result<Option<Charset>, IoError> {
+    let mime = match extension.map(Extension::mime).filter(|mime| mime.is_text()) {
+        Some(mime) => mime,
+        None => return Ok(None),
+    };
+    // A byte past the prefix tells whether there's more to the file.
+    let mut prefix = Vec::new();
+    let _ = file.take(PREFIX_LEN + 1).read_to_end(&mut prefix)?;
+    let _ = file.seek(SeekFrom::Start(0))?;
+    let partial = prefix.len() as u64 > PREFIX_LEN;
+
+    if partial {
+        let _ = prefix.pop();
+    }
+
+    Ok(Charset::detect(
+        mime,
+        &prefix,
+        partial,
+        env.default_charset(),
+    ))
+}
+
+/// Find the precompressed sidecar files of a file, returning whether any
+/// exist and the opened sidecar most acceptable to a request.
+///
+/// Sidecars are only served for files of known types, since the type of
+/// compressed content can't be sniffed.
+fn sidecar(
+    request: &Request<'_>,
+    path: &Path,
+    extension: Option<Extension>,
+) -> (bool, Option<(Encoding, File)>) {
+    if extension.is_none() {
+        return (false, None);
+    }
+
+    let available = sidecars(path);
+    let sidecar = request
+        .header("Accept-Encoding")
+        .and_then(|accept| Encoding::negotiate(accept, &available))
+        .and_then(|encoding| {
+            let file = File::open(sidecar_path(path, encoding)?).ok()?;
+
+            Some((encoding, file))
+        });
+
+    (!available.is_empty(), sidecar)
+}
+
+/// Encodings of the precompressed sidecar files of a file that exist.
+///
+/// Sidecars older than the file are stale, and are ignored until they're
+/// compressed again.
+fn sidecars(path: &Path) -> Vec<Encoding> {
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    Encoding::PRECOMPRESSED
+        .iter()
+        .copied()
+        .filter(|encoding| {
+            sidecar_path(path, *encoding)
+                .and_then(|path| fs::metadata(path).ok())
+                .filter(Metadata::is_file)
+                .map_or(false, |metadata| metadata.modified().ok() >= modified)
+        })
+        .collect()
+}
+
+/// Path of the sidecar of a file precompressed with an encoding, such as
+/// `app.js.br` for `app.js`.
+fn sidecar_path(path: &Path, encoding: Encoding) -> Option<PathBuf> {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".");
+    sidecar.push(encoding.sidecar_extension()?);
+
+    Some(sidecar.into())
+}
+
+/// Send the preload hints of an HTML page in an interim response ahead of
+/// the page.
+///
+/// Hints can only be sent ahead of time when they're known without reading
+/// the file, and interim responses aren't understood by HTTP/1.0 clients.
+fn early_hints(
+    stream: &mut TcpStream,
+    request: &Request<'_>,
+    env: &Environment,
+    state: &mut State,
+    path: &Path,
+    modified: Option<SystemTime>,
+) -> Result<(), GetError> {
+    if !env.early_hints() || request.version() != Version::Http11 {
+        return Ok(());
+    }
+
+    let cached = modified.and_then(|modified| state.hints_mut().get(path, modified));
+    let hints = match cached {
+        Some(hints) if !hints.is_empty() => hints,
+        _ => return Ok(()),
+    };
+    let mut response = Response::new(Status::EARLY_HINTS);
+
+    for hint in hints {
+        response.headers_mut().append("Link", hint.to_string());
+    }
+
+    response
+        .write(stream)
+        .map_err(|source| GetError::Write { source })
+}
+
+/// Respond to a request early if its preconditions mean the content won't be
+/// sent.
+fn precondition(request: &Request<'_>, variant: &Variant<'_>) -> Option<Response<'static>> {
+    let status = match conditional::evaluate(request, variant.etag.as_ref(), variant.modified) {
+        Outcome::NotModified => Status::NOT_MODIFIED,
+        Outcome::PreconditionFailed => Status::PRECONDITION_FAILED,
+        Outcome::Proceed => return None,
+    };
+
+    Some(variant.response(status))
+}
+
+fn error(source: &IoError) -> Response<'static> {
+    let status = match source.kind() {
+        ErrorKind::NotFound => Status::NOT_FOUND,
+        ErrorKind::PermissionDenied => Status::FORBIDDEN,
+        _ => Status::INTERNAL_SERVER_ERROR,
+    };
+
+    Response::new(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::super::{
+            compress::Encoding, content_type::Mime, env::Environment, etag::ETag,
+            file_cache::FileCache, headers_file::HeadersFile, response::Body, state::State,
+        },
+        get, sidecar, Request, Variant,
+    };
+    use std::{
+        env,
+        error::Error,
+        fs,
+        io::Read,
+        net::{Ipv4Addr, TcpListener, TcpStream},
+        path::Path,
+        thread,
+        time::Duration,
+    };
+
+    /// Encoding of the sidecar of a file chosen for an `Accept-Encoding`
+    /// header, along with whether the file has any sidecars.
+    fn chosen(path: &Path, accept_encoding: &str) -> (bool, Option<Encoding>) {
+        let input = format!("GET / HTTP/1.1\r\nAccept-Encoding: {accept_encoding}\r\n\r\n");
+        let request = Request::from_input(input.as_bytes()).unwrap();
+        let extension = super::extension(path);
+        let (has_sidecars, sidecar) = sidecar(&request, path, extension);
+
+        (has_sidecars, sidecar.map(|(encoding, _)| encoding))
+    }
+
+    #[test]
+    fn test_sidecar() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-sidecar-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("app.js"), "plain")?;
+        fs::write(dir.join("app"), "plain")?;
+        fs::write(dir.join("old.css.gz"), "stale")?;
+        // Modification times need to differ for staleness to be noticed.
+        thread::sleep(Duration::from_millis(20));
+        fs::write(dir.join("old.css"), "plain")?;
+
+        for name in ["app.js.br", "app.js.zst", "app.js.gz", "app.gz"] {
+            fs::write(dir.join(name), "compressed")?;
         }
+
+        let app = dir.join("app.js");
+        assert_eq!(
+            (true, Some(Encoding::Brotli)),
+            chosen(&app, "gzip, br, zstd")
+        );
+        assert_eq!(
+            (true, Some(Encoding::Zstd)),
+            chosen(&app, "gzip;q=0.5, br;q=0.1, zstd;q=0.8")
+        );
+        assert_eq!((true, Some(Encoding::Gzip)), chosen(&app, "gzip"));
+        assert_eq!((true, None), chosen(&app, "br;q=0, identity"));
+        // Files of unknown types and stale sidecars are never served.
+        assert_eq!((false, None), chosen(&dir.join("app"), "gzip"));
+        assert_eq!((false, None), chosen(&dir.join("old.css"), "gzip"));
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_precompressed_response() {
+        let mut response = Variant::new(Mime::TextCss, None, None, None)
+            .precompressed(Encoding::Brotli)
+            .ok(Body::Bytes(b"compressed".as_ref().into()), &[]);
+        let headers = response.headers_mut();
+
+        assert_eq!(Some("br"), headers.get("Content-Encoding"));
+        assert_eq!(Some("Accept-Encoding"), headers.get("Vary"));
+        assert_eq!(Some("text/css"), headers.get("Content-Type"));
+    }
+
+    /// Response to a request for a file, as it's written to the client.
+    fn respond(
+        env: &Environment,
+        input: &str,
+        path: &Path,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let request = Request::from_input(input.as_bytes()).unwrap();
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let mut client = TcpStream::connect(listener.local_addr()?)?;
+        let (mut stream, _) = listener.accept()?;
+        let mut state = State::new(HeadersFile::default(), FileCache::new(0, 0));
+        get(&mut stream, &request, path, env, &mut state)?.write(&mut stream)?;
+        drop(stream);
+
+        let mut output = String::new();
+        let _ = client.read_to_string(&mut output)?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn test_if_match_hash() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-get-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("a.txt"), "test")?;
+
+        let args = ["--dir", dir.to_str().unwrap(), "--etag", "hash"];
+        let env = Environment::from_args(args.iter().map(|&x| x.to_owned()))?;
+        let status = |if_match: &str| -> Result<String, Box<dyn Error + Send + Sync>> {
+            let input = format!("GET /a.txt HTTP/1.1\r\nIf-Match: {if_match}\r\n\r\n");
+            let output = respond(&env, &input, &dir.join("a.txt"))?;
+
+            Ok(output.split(' ').nth(1).unwrap_or_default().to_owned())
+        };
+
+        // Tags from the content are only known once the file has been read.
+        let tag = ETag::from_content(b"test").to_string();
+        assert_eq!("200", status(&tag)?);
+        assert_eq!("412", status("\"a\"")?);
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_control_path() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-cache-path-{}", std::process::id()));
+        fs::create_dir_all(dir.join("docs"))?;
+        fs::write(dir.join("docs/index.html"), "index")?;
+
+        let args = [
+            "--dir",
+            dir.to_str().unwrap(),
+            "--cache",
+            "*.html=no-cache",
+            "--cache",
+            "docs/=max-age=60",
+        ];
+        let env = Environment::from_args(args.iter().map(|&x| x.to_owned()))?;
+        let index = dir.join("docs/index.html");
+
+        // Rules match the path of the request rather than the file served.
+        let output = respond(&env, "GET /docs/ HTTP/1.1\r\n\r\n", &index)?;
+        assert!(output.contains("\r\nCache-Control: max-age=60\r\n"));
+        let output = respond(&env, "GET /docs/index.html HTTP/1.1\r\n\r\n", &index)?;
+        assert!(output.contains("\r\nCache-Control: no-cache\r\n"));
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
     }
 }
@@ -1,31 +1,25 @@
-use super::super::response::{Response, WriteError};
+use super::super::{
+    charset::Charset,
+    content_type::Mime,
+    percent,
+    response::{Body, Headers, Response, Status},
+};
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use std::{
     error::Error,
     fs,
-    io::Error as IoError,
-    net::{SocketAddr, TcpStream},
+    io::{Error as IoError, Write},
     path::PathBuf,
+    time::Instant,
 };
 
+// Every error is a failure to read part of the directory.
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum IndexError {
-    ReadingDirectory {
-        path: PathBuf,
-        source: IoError,
-    },
-    ReadingEntry {
-        source: IoError,
-    },
-    ReadingMetadata {
-        path: PathBuf,
-        source: IoError,
-    },
-    WritingToStream {
-        buf: String,
-        remote_ip: Option<SocketAddr>,
-        source: WriteError,
-    },
+    ReadingDirectory { path: PathBuf, source: IoError },
+    ReadingEntry { source: IoError },
+    ReadingMetadata { path: PathBuf, source: IoError },
 }
 
 impl Display for IndexError {
@@ -42,29 +36,24 @@ impl Display for IndexError {
                 f.write_str(&path.to_string_lossy())?;
                 f.write_str("'")
             }
-            Self::WritingToStream { buf, remote_ip, .. } => {
-                f.write_str("failed to write to remote (")?;
-
-                if let Some(ip) = remote_ip {
-                    f.write_str("'")?;
-                    ip.fmt(f)?;
-                    f.write_str("'")?;
-                } else {
-                    f.write_str("unknown")?;
-                }
-
-                f.write_str("); content: ")?;
-                f.write_str(buf)
-            }
         }
     }
 }
 
-impl Error for IndexError {}
-
-pub fn index(stream: &mut TcpStream, path: PathBuf) -> Result<(), IndexError> {
-    let mut buf = String::new();
+impl Error for IndexError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ReadingDirectory { source, .. }
+            | Self::ReadingEntry { source }
+            | Self::ReadingMetadata { source, .. } => Some(source),
+        }
+    }
+}
 
+/// List the entries of a directory, other than hidden ones and those with
+/// one of the names to leave out.
+pub fn index(path: PathBuf, hidden: &[&str]) -> Result<Response<'static>, IndexError> {
+    let start = Instant::now();
     let mut dirs = Vec::new();
     let mut files = Vec::new();
 
@@ -88,13 +77,14 @@ pub fn index(stream: &mut TcpStream, path: PathBuf) -> Result<(), IndexError> {
             continue;
         };
 
-        // Skip hidden directories, `.`, and `..`.
-        if name.starts_with('.') {
+        // Skip hidden directories, `.`, `..`, and files that aren't served.
+        if name.starts_with('.') || hidden.contains(&name) {
             continue;
         }
 
         if metadata.is_dir() {
-            dirs.push(name.to_owned());
+            // Links to directories end with a slash to avoid a redirect.
+            dirs.push(format!("{name}/"));
         } else {
             files.push(name.to_owned());
         }
@@ -103,36 +93,62 @@ pub fn index(stream: &mut TcpStream, path: PathBuf) -> Result<(), IndexError> {
     dirs.sort();
     files.sort();
 
+    let body = Body::Stream(Box::new(move |buf| {
+        write_listing(buf, &dirs, &files)?;
+
+        let mut trailers = Headers::default();
+        trailers.append(
+            "Server-Timing",
+            format!("index;dur={:.3}", start.elapsed().as_secs_f64() * 1000.0),
+        );
+
+        Ok(trailers)
+    }));
+
+    Ok(Response::new(Status::OK)
+        .header(
+            "Content-Type",
+            Mime::TextHtml.with_charset(Some(Charset::Utf8)).to_string(),
+        )
+        .header("Trailer", "Server-Timing")
+        .body(body))
+}
+
+fn write_listing(buf: &mut dyn Write, dirs: &[String], files: &[String]) -> Result<(), IoError> {
     if !dirs.is_empty() {
-        buf.push_str("<h2>directories</h2>");
+        buf.write_all(b"<h2>directories</h2>")?;
 
         for dir in dirs {
-            write_anchor(&mut buf, &dir);
+            write_anchor(buf, dir, false)?;
         }
     }
 
     if !files.is_empty() {
-        buf.push_str("<h2>files</h2>");
+        buf.write_all(b"<h2>files</h2>")?;
 
         for file in files {
-            write_anchor(&mut buf, &file);
+            write_anchor(buf, file, true)?;
         }
     }
 
-    Response::new(buf.as_bytes())
-        .ok()
-        .write(stream)
-        .map_err(|source| IndexError::WritingToStream {
-            buf,
-            remote_ip: stream.peer_addr().ok(),
-            source,
-        })
+    Ok(())
 }
 
-fn write_anchor(buf: &mut String, path: &str) {
-    buf.push_str("<a href='./");
-    buf.push_str(path);
-    buf.push_str("'>");
-    buf.push_str(path);
-    buf.push_str("</a><br />");
+/// Write a link to an entry, followed by a link to download it for files.
+fn write_anchor(buf: &mut dyn Write, path: &str, download: bool) -> Result<(), IoError> {
+    let href = percent::encode_path(path);
+
+    buf.write_all(b"<a href='./")?;
+    buf.write_all(href.as_bytes())?;
+    buf.write_all(b"'>")?;
+    buf.write_all(path.as_bytes())?;
+    buf.write_all(b"</a>")?;
+
+    if download {
+        buf.write_all(b" <a href='./")?;
+        buf.write_all(href.as_bytes())?;
+        buf.write_all(b"?download' title='download' download>&#x2913;</a>")?;
+    }
+
+    buf.write_all(b"<br />")
 }
@@ -1,18 +1,36 @@
+mod conditional;
 mod get;
 mod index;
+mod options;
 
 use self::{get::GetError, index::IndexError};
 use super::{
+    accept,
+    content_type::Extension,
+    custom_headers,
     env::Environment,
-    response::{Response, WriteError},
+    error_page, headers_file,
+    negotiate::{self, Outcome},
+    percent,
+    redirects::{self, Match},
+    response::{Headers, Response, Status, WriteError},
+    state::State,
 };
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use std::{
+    borrow::Cow,
     error::Error,
+    ffi::OsStr,
+    fs::{self, Metadata},
     io::{Error as IoError, Read},
     net::TcpStream,
+    path::{Component, Path, PathBuf},
 };
 
+/// Names of the configuration files read from the served directory, which
+/// are neither served nor listed.
+const CONFIG_FILES: &[&str] = &[headers_file::FILE_NAME, redirects::FILE_NAME];
+
 #[derive(Debug)]
 pub enum RequestError {
     Get { source: GetError },
@@ -27,60 +45,563 @@ impl Display for RequestError {
     }
 }
 
-impl Error for RequestError {}
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Get { source } => Some(source),
+            Self::Index { source } => Some(source),
+            Self::ReadFromStream { source } => Some(source),
+            Self::Write { source } => Some(source),
+        }
+    }
+}
 
-#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
 pub enum RequestedMethod {
     Get,
+    Head,
+    Options,
 }
 
 impl RequestedMethod {
+    /// Methods that are allowed, for use in the `Allow` header.
+    const ALLOWED: &'static str = "GET, HEAD, OPTIONS";
+
     fn from_input(buf: &[u8]) -> Option<Self> {
         match buf.split(|x| *x == b' ').next() {
             Some(b"GET") => Some(Self::Get),
+            Some(b"HEAD") => Some(Self::Head),
+            Some(b"OPTIONS") => Some(Self::Options),
+            _ => None,
+        }
+    }
+}
+
+/// HTTP version of a request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Version {
+    Http10,
+    Http11,
+}
+
+impl Version {
+    fn from_input(buf: &[u8]) -> Option<Self> {
+        match buf {
+            b"HTTP/1.0" => Some(Self::Http10),
+            _ if buf.starts_with(b"HTTP/1.") => Some(Self::Http11),
             _ => None,
         }
     }
 }
 
-pub fn handle(stream: &mut TcpStream, env: &Environment) -> Result<(), RequestError> {
-    let mut buf = [0; 1024];
+/// Parsed request line of a request.
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub struct Request<'a> {
+    headers: &'a [u8],
+    method: RequestedMethod,
+    target: &'a [u8],
+    version: Version,
+}
+
+impl<'a> Request<'a> {
+    fn from_input(buf: &'a [u8]) -> Option<Self> {
+        let line_len = buf.iter().position(|x| *x == b'\n').unwrap_or(buf.len());
+        let line = trim(&buf[..line_len]);
+        let headers = buf.get(line_len + 1..).unwrap_or_default();
+        let mut parts = line.split(|x| *x == b' ');
+
+        let method = RequestedMethod::from_input(parts.next()?)?;
+        // Only OPTIONS requests may target the server as a whole.
+        let target = parts.next().filter(|target| {
+            (target.starts_with(b"/") || (method == RequestedMethod::Options && *target == b"*"))
+                && !target.iter().any(u8::is_ascii_control)
+        })?;
+        let version = Version::from_input(parts.next()?)?;
+
+        Some(Self {
+            headers,
+            method,
+            target,
+            version,
+        })
+    }
+
+    /// Retrieve the value of the first header with a case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<&'a [u8]> {
+        self.headers
+            .split(|x| *x == b'\n')
+            .map(trim)
+            .take_while(|line| !line.is_empty())
+            .find_map(|line| {
+                let colon = line.iter().position(|x| *x == b':')?;
+
+                line[..colon]
+                    .eq_ignore_ascii_case(name.as_bytes())
+                    .then(|| trim(&line[colon + 1..]))
+            })
+    }
+
+    // Retrieve the decoded path of the request target relative to the
+    // served directory, without the leading slash or query.
+    pub fn relative_path(&self) -> Cow<'a, str> {
+        let path = self.target.split(|x| *x == b'?').next().unwrap_or_default();
+
+        match percent::decode(path.get(1..).unwrap_or_default()) {
+            Cow::Borrowed(path) => String::from_utf8_lossy(path),
+            Cow::Owned(path) => Cow::Owned(String::from_utf8_lossy(&path).into_owned()),
+        }
+    }
+
+    // Retrieve the path of the request target as sent, without the query.
+    pub fn path(&self) -> Cow<'a, str> {
+        String::from_utf8_lossy(self.target.split(|x| *x == b'?').next().unwrap_or_default())
+    }
+
+    // Retrieve the request target with its path replaced, keeping the query.
+    pub fn location(&self, path: &str) -> String {
+        let mut location = path.to_owned();
+
+        if let Some(query) = self.query() {
+            location.push('?');
+            location.push_str(&String::from_utf8_lossy(query));
+        }
+
+        location
+    }
+
+    // Retrieve the query of the request target, if any.
+    pub fn query(&self) -> Option<&'a [u8]> {
+        let mut parts = self.target.splitn(2, |x| *x == b'?');
+        let _ = parts.next();
+
+        parts.next()
+    }
+
+    // Method of the request.
+    pub const fn method(&self) -> RequestedMethod {
+        self.method
+    }
+
+    // HTTP version of the request.
+    pub const fn version(&self) -> Version {
+        self.version
+    }
+}
+
+pub fn handle(
+    stream: &mut TcpStream,
+    env: &Environment,
+    state: &mut State,
+) -> Result<(), RequestError> {
+    // Responses go out in as few writes as possible, so there's nothing for
+    // Nagle's algorithm to coalesce and waiting on it only adds latency. For
+    // the same reason the socket isn't corked: the head already leaves in the
+    // same write as the start of the content.
+    let _ = stream.set_nodelay(true);
 
-    let _ = stream
-        .read(&mut buf)
-        .map_err(|source| RequestError::ReadFromStream { source })?;
+    let mut buf = [0; 8192];
+    let mut len = 0;
 
-    if RequestedMethod::from_input(&buf).is_none() {
-        return Response::new(b"")
-            .method_not_allowed(&[b"GET"])
-            .write(stream)
-            .map_err(|source| RequestError::Write { source });
+    // Read until the end of the headers, which may arrive over multiple reads.
+    loop {
+        let read = stream
+            .read(&mut buf[len..])
+            .map_err(|source| RequestError::ReadFromStream { source })?;
+        len += read;
+
+        if read == 0 || len == buf.len() || buf[..len].windows(4).any(|x| x == b"\r\n\r\n") {
+            break;
+        }
     }
 
-    let part = buf[5..].split(|x| *x == b'\r').next().unwrap();
-    let relative = &part[..part.len() - 9];
+    let buf = &buf[..len];
+
+    if RequestedMethod::from_input(buf).is_none() {
+        let response =
+            Response::new(Status::METHOD_NOT_ALLOWED).header("Allow", RequestedMethod::ALLOWED);
 
-    let path = if relative == b"/" {
-        env.dir_ref().to_owned()
+        return respond(stream, response, None, env, state);
+    }
+
+    let request = if let Some(request) = Request::from_input(buf) {
+        request
     } else {
-        let mut path = env.dir_ref().to_owned();
-        path.push(String::from_utf8_lossy(relative).as_ref());
+        let response = Response::new(Status::BAD_REQUEST);
 
-        path
+        return respond(stream, response, None, env, state);
     };
 
-    let path_str = path.to_str().unwrap().trim_end();
+    if request.method() == RequestedMethod::Options {
+        let response = options::options(&request, env);
+
+        return respond(stream, response, Some(&request), env, state);
+    }
+
+    // Decoded paths are checked before anything touches the file system, so
+    // that requests such as `/%2e%2e/` can't escape the served directory.
+    if !is_contained(&request.relative_path()) {
+        let response = Response::new(Status::BAD_REQUEST);
 
-    if env.index() && path_str.ends_with('/') {
-        return index::index(stream, path).map_err(|source| RequestError::Index { source });
+        return respond(stream, response, None, env, state);
     }
 
-    get::get(stream, &path).map_err(|source| RequestError::Get { source })
+    let response = route(stream, &request, env, state)?;
+
+    // HTTP/1.0 clients don't understand chunked content.
+    let response = response
+        .chunked(request.version() == Version::Http11)
+        .head(request.method() == RequestedMethod::Head);
+
+    respond(stream, response, Some(&request), env, state)
+}
+
+/// Respond with the target of the first rule matching the request, or with
+/// what its path resolves to in the served directory.
+fn route(
+    stream: &mut TcpStream,
+    request: &Request<'_>,
+    env: &Environment,
+    state: &mut State,
+) -> Result<Response<'static>, RequestError> {
+    let relative_path = request.relative_path();
+    let exists = env.dir_ref().join(relative_path.as_ref()).exists();
+    let path = format!("/{relative_path}");
+    let rule = redirects::find(env.redirects_ref(), &path, request.query(), exists);
+
+    let Match { status, target } = if let Some(rule) = rule {
+        rule
+    } else {
+        return resolve(stream, request, &relative_path, env, state);
+    };
+
+    if status.is_redirection() {
+        // The query of the request carries over unless the target has its own.
+        let location = if target.contains('?') {
+            target
+        } else {
+            request.location(&target)
+        };
+
+        return Ok(Response::redirect(status, location));
+    }
+
+    // Rewrites serve the target in place of the path without a redirect, as
+    // long as values captured from the query haven't taken it elsewhere.
+    let target = target.split('?').next().unwrap_or_default();
+
+    if !is_contained(target.trim_start_matches('/')) {
+        return Ok(Response::new(Status::FORBIDDEN));
+    }
+
+    let response = resolve(stream, request, target.trim_start_matches('/'), env, state)?;
+
+    Ok(if response.status() == Status::OK {
+        response.status_code(status)
+    } else {
+        response
+    })
+}
+
+/// Respond with the file, directory, or fallback a path relative to the
+/// served directory resolves to.
+fn resolve(
+    stream: &mut TcpStream,
+    request: &Request<'_>,
+    relative_path: &str,
+    env: &Environment,
+    state: &mut State,
+) -> Result<Response<'static>, RequestError> {
+    let mut path = env.dir_ref().to_owned();
+    path.push(relative_path);
+
+    if is_config_file(env.dir_ref(), &path) {
+        return Ok(Response::new(Status::NOT_FOUND));
+    }
+
+    let mut metadata = fs::metadata(&path).ok();
+    let trailing_slash = relative_path.is_empty() || relative_path.ends_with('/');
+
+    if let Some(page) = Some(&path)
+        .filter(|_| env.clean_urls() && !trailing_slash)
+        .and_then(|path| clean_url(path, metadata.as_ref()))
+    {
+        metadata = fs::metadata(&page).ok();
+        path = page;
+    }
+
+    let is_dir = metadata.as_ref().map_or(false, Metadata::is_dir);
+    let clean_location = Some(request.path())
+        .filter(|_| env.clean_urls_redirect() && metadata.is_some() && !is_dir)
+        .and_then(|path| clean_path(&path));
+
+    let response = if let Some(location) = clean_location {
+        Response::redirect(Status::MOVED_PERMANENTLY, request.location(&location))
+    } else if is_dir {
+        match directory(&path, trailing_slash, env.index_files_ref(), env.index()) {
+            Directory::Redirect => {
+                let location = request.location(&format!("{}/", request.path()));
+
+                Response::redirect(Status::MOVED_PERMANENTLY, location)
+            }
+            Directory::IndexFile(index_file) => get::get(stream, request, &index_file, env, state)
+                .map_err(|source| RequestError::Get { source })?,
+            Directory::Listing => {
+                let hidden = if path == env.dir_ref() {
+                    CONFIG_FILES
+                } else {
+                    &[]
+                };
+
+                index::index(path, hidden).map_err(|source| RequestError::Index { source })?
+            }
+            Directory::NotFound => Response::new(Status::NOT_FOUND),
+        }
+    } else if let Some(outcome) = Some(&path).filter(|_| env.multiviews()).and_then(|path| {
+        negotiate::negotiate(
+            path,
+            request.header("Accept"),
+            request.header("Accept-Language"),
+        )
+    }) {
+        variant(stream, request, outcome, env, state)?
+    } else if let Some(fallback) = env
+        .spa_ref()
+        .filter(|_| metadata.is_none() && is_route(&path))
+    {
+        // Missing pages are routes of the application when HTML is accepted.
+        let accepts_html = request
+            .header("Accept")
+            .map_or(false, |accept| accept::includes(accept, "text/html"));
+        let path = if accepts_html {
+            env.dir_ref().join(fallback)
+        } else {
+            path
+        };
+
+        get::get(stream, request, &path, env, state)
+            .map_err(|source| RequestError::Get { source })?
+            .header("Vary", "Accept")
+    } else {
+        get::get(stream, request, &path, env, state)
+            .map_err(|source| RequestError::Get { source })?
+    };
+
+    Ok(response)
+}
+
+/// Respond with the variant of a path chosen by negotiation, along with its
+/// language and location, or with `406 Not Acceptable` if none are
+/// acceptable.
+fn variant(
+    stream: &mut TcpStream,
+    request: &Request<'_>,
+    outcome: Outcome,
+    env: &Environment,
+    state: &mut State,
+) -> Result<Response<'static>, RequestError> {
+    let (mut response, vary) = match outcome {
+        Outcome::NotAcceptable { vary } => (Response::new(Status::NOT_ACCEPTABLE), vary),
+        Outcome::Variant { variant, vary } => {
+            let mut response = get::get(stream, request, variant.path_ref(), env, state)
+                .map_err(|source| RequestError::Get { source })?;
+
+            if let Some(language) = variant.language_ref() {
+                response = response.header("Content-Language", language);
+            }
+
+            if let Some(path) = variant
+                .path_ref()
+                .strip_prefix(env.dir_ref())
+                .ok()
+                .and_then(Path::to_str)
+            {
+                let location = percent::encode_path(&format!("/{path}"));
+                response = response.header("Content-Location", location);
+            }
+
+            (response, vary)
+        }
+    };
+
+    if let Some(vary) = vary {
+        response = response.header("Vary", vary);
+    }
+
+    Ok(response)
+}
+
+/// Resolve the path of a request without a trailing slash to an HTML page
+/// with a clean URL, trying `<path>.html` if nothing exists at the path.
+///
+/// Directories are left to be redirected to their path with a trailing
+/// slash, which serves their `index.html`.
+fn clean_url(path: &Path, metadata: Option<&Metadata>) -> Option<PathBuf> {
+    if metadata.is_some() {
+        return None;
+    }
+
+    let mut html = path.as_os_str().to_owned();
+    html.push(".html");
+
+    Some(PathBuf::from(html)).filter(|path| path.is_file())
+}
+
+/// Clean form of the path of a request for an HTML page, without its
+/// extension or index file name, if it isn't already clean.
+fn clean_path(path: &str) -> Option<String> {
+    let path = path.strip_suffix(".html")?;
+
+    Some(match path.strip_suffix("index") {
+        Some(dir) if dir.ends_with('/') => dir.to_owned(),
+        _ => path.to_owned(),
+    })
+}
+
+/// Whether a path is that of one of the configuration files in the root of
+/// the served directory.
+fn is_config_file(dir: &Path, path: &Path) -> bool {
+    path.parent() == Some(dir)
+        && path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map_or(false, |name| CONFIG_FILES.contains(&name))
+}
+
+/// How a request for a directory is answered.
+#[derive(Debug, Eq, PartialEq)]
+enum Directory {
+    /// Redirect to the path with a trailing slash, since relative links
+    /// within a directory only resolve against it when its path has one.
+    Redirect,
+    /// Serve the first of the index files that exists in the directory.
+    IndexFile(PathBuf),
+    /// Generate a listing of the directory.
+    Listing,
+    /// Answer as if nothing exists at the path.
+    NotFound,
+}
+
+/// Decide how to answer a request for a directory, falling back to a
+/// listing if enabled when it has none of the index files.
+fn directory(dir: &Path, trailing_slash: bool, index_files: &[String], listing: bool) -> Directory {
+    if !trailing_slash {
+        return Directory::Redirect;
+    }
+
+    let index_file = index_files
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file());
+
+    match index_file {
+        Some(path) => Directory::IndexFile(path),
+        None if listing => Directory::Listing,
+        None => Directory::NotFound,
+    }
+}
+
+/// Whether a path may be a route of a single-page application rather than a
+/// missing asset, which it is unless it has the extension of a known type
+/// other than HTML.
+fn is_route(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .and_then(Extension::new)
+        .map_or(true, |extension| matches!(extension, Extension::Html))
+}
+
+/// Write a response preceded by the header fields sent with every response,
+/// and followed by the custom fields configured for the request.
+fn respond(
+    stream: &mut TcpStream,
+    response: Response<'_>,
+    request: Option<&Request<'_>>,
+    env: &Environment,
+    state: &mut State,
+) -> Result<(), RequestError> {
+    let accept = request.and_then(|request| request.header("Accept"));
+    let response = error_page::apply(response, accept, env.error_pages_ref(), env.dir_ref());
+    let mut defaults = Headers::default();
+
+    if env.server_header() {
+        defaults.append("Server", Response::SERVER);
+    }
+
+    defaults.append("Date", state.date_mut().now());
+    // Connections aren't kept alive, and ranges of files aren't supported.
+    defaults.append("Connection", "close");
+    defaults.append("Accept-Ranges", "none");
+    defaults.append_all(env.security_headers_ref().clone());
+
+    // Custom fields may replace the defaults.
+    let mut response = response.defaults(defaults);
+    let path = request.map(Request::relative_path);
+    custom_headers::apply(
+        env.headers_ref(),
+        env.header_rules_ref(),
+        path.as_deref(),
+        response.headers_mut(),
+    );
+
+    // Rules of the _headers file apply over custom fields, as in production.
+    if let Some(path) = path.as_deref() {
+        let headers_file = state.headers_file_mut();
+        headers_file.reload();
+        headers_file.apply(path, response.headers_mut());
+    }
+
+    // Preflight responses already describe which origins are allowed.
+    if let Some((cors, request)) = env
+        .cors_ref()
+        .zip(request)
+        .filter(|(_, request)| request.method() != RequestedMethod::Options)
+    {
+        cors.apply(request.header("Origin"), response.headers_mut());
+    }
+
+    response
+        .write(stream)
+        .map_err(|source| RequestError::Write { source })
+}
+
+/// Whether a decoded path relative to the served directory stays within it,
+/// which it doesn't if it has parent or root components, or a NUL byte.
+pub fn is_contained(relative_path: &str) -> bool {
+    !relative_path.contains('\0')
+        && Path::new(relative_path)
+            .components()
+            .all(|component| matches!(component, Component::CurDir | Component::Normal(_)))
+}
+
+/// Trim leading and trailing ASCII whitespace.
+fn trim(mut buf: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = buf {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+
+        buf = rest;
+    }
+
+    while let [rest @ .., last] = buf {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+
+        buf = rest;
+    }
+
+    buf
 }
 
 #[cfg(test)]
 mod tests {
-    use super::RequestedMethod;
+    use super::{
+        clean_path, clean_url, directory, is_config_file, is_contained, Directory, Request,
+        RequestedMethod, Version,
+    };
+    use std::{env, error::Error, fs, path::Path};
 
     #[test]
     fn test_method() {
@@ -89,4 +610,176 @@ mod tests {
             RequestedMethod::from_input(b"GET /test.html\r\n")
         );
     }
+
+    #[test]
+    fn test_request() {
+        let request = Request::from_input(b"GET /a.html HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert_eq!(
+            Some(Request {
+                headers: b"Host: localhost\r\n\r\n",
+                method: RequestedMethod::Get,
+                target: b"/a.html",
+                version: Version::Http11,
+            }),
+            request
+        );
+
+        assert_eq!(
+            Some(Version::Http10),
+            Request::from_input(b"GET / HTTP/1.0\r\n\r\n").map(|request| request.version())
+        );
+        assert_eq!(
+            Some(RequestedMethod::Head),
+            Request::from_input(b"HEAD / HTTP/1.1\r\n\r\n").map(|request| request.method())
+        );
+        assert_eq!(
+            Some("a/b/c.html".into()),
+            Request::from_input(b"GET /a/b/c.html?d=e HTTP/1.1\r\n")
+                .map(|request| request.relative_path())
+        );
+        assert_eq!(
+            Some("a b/é.txt".into()),
+            Request::from_input(b"GET /a%20b/%C3%A9.txt HTTP/1.1\r\n")
+                .map(|request| request.relative_path())
+        );
+        assert_eq!(
+            Some(Some(b"download&v=1?".as_ref())),
+            Request::from_input(b"GET /a.txt?download&v=1? HTTP/1.1\r\n")
+                .map(|request| request.query())
+        );
+        assert_eq!(
+            Some(b"*".as_ref()),
+            Request::from_input(b"OPTIONS * HTTP/1.1\r\n").map(|request| request.target)
+        );
+        assert_eq!(
+            Some("/docs/?v=1".to_owned()),
+            Request::from_input(b"GET /docs?v=1 HTTP/1.1\r\n")
+                .map(|request| request.location(&format!("{}/", request.path())))
+        );
+        assert!(Request::from_input(b"GET * HTTP/1.1\r\n").is_none());
+        assert!(Request::from_input(b"GET /a\rb HTTP/1.1\r\n").is_none());
+        assert!(Request::from_input(b"GET a.html HTTP/1.1\r\n").is_none());
+        assert!(Request::from_input(b"GET /a.html\r\n").is_none());
+    }
+
+    #[test]
+    fn test_is_contained() {
+        let contained = |target: &[u8]| {
+            let mut input = b"GET ".to_vec();
+            input.extend_from_slice(target);
+            input.extend_from_slice(b" HTTP/1.1\r\n\r\n");
+
+            is_contained(&Request::from_input(&input).unwrap().relative_path())
+        };
+
+        assert!(contained(b"/"));
+        assert!(contained(b"/a/b.html"));
+        assert!(contained(b"/a%20b/./c.txt"));
+        assert!(!contained(b"/%2ftmp/secret.txt"));
+        assert!(!contained(b"/%2e%2e/secret.txt"));
+        assert!(!contained(b"/a/..%2f..%2fsecret.txt"));
+        assert!(!contained(b"/../secret.txt"));
+        assert!(!contained(b"//tmp/secret.txt"));
+        assert!(!contained(b"/a%00.html"));
+    }
+
+    #[test]
+    fn test_directory() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-directory-{}", std::process::id()));
+        fs::create_dir_all(dir.join("site"))?;
+        fs::create_dir_all(dir.join("empty"))?;
+        fs::write(dir.join("site/index.htm"), "index")?;
+        fs::write(dir.join("site/default.html"), "default")?;
+        let site = dir.join("site");
+        let defaults = ["index.html".to_owned(), "index.htm".to_owned()];
+        let custom = ["home.html".to_owned(), "default.html".to_owned()];
+
+        assert_eq!(
+            Directory::IndexFile(site.join("index.htm")),
+            directory(&site, true, &defaults, false)
+        );
+        // The first configured index file that exists is used.
+        assert_eq!(
+            Directory::IndexFile(site.join("default.html")),
+            directory(&site, true, &custom, true)
+        );
+        assert_eq!(
+            Directory::Listing,
+            directory(&dir.join("empty"), true, &defaults, true)
+        );
+        assert_eq!(
+            Directory::NotFound,
+            directory(&dir.join("empty"), true, &defaults, false)
+        );
+        // Directories are redirected to a trailing slash before index files
+        // are looked for.
+        assert_eq!(
+            Directory::Redirect,
+            directory(&site, false, &defaults, true)
+        );
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_config_file() {
+        let dir = Path::new("/srv/site");
+
+        assert!(is_config_file(dir, &dir.join("_redirects")));
+        assert!(is_config_file(dir, &dir.join("./_redirects")));
+        assert!(is_config_file(dir, &dir.join("_headers")));
+        assert!(!is_config_file(dir, &dir.join("docs/_redirects")));
+        assert!(!is_config_file(dir, &dir.join("redirects")));
+    }
+
+    #[test]
+    fn test_clean_path() {
+        assert_eq!(Some("/about".to_owned()), clean_path("/about.html"));
+        assert_eq!(Some("/docs/".to_owned()), clean_path("/docs/index.html"));
+        assert_eq!(Some("/".to_owned()), clean_path("/index.html"));
+        assert_eq!(None, clean_path("/about"));
+        assert_eq!(None, clean_path("/app.js"));
+        assert_eq!(Some("/reindex".to_owned()), clean_path("/reindex.html"));
+    }
+
+    #[test]
+    fn test_clean_url() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dir = env::temp_dir().join(format!("picoserve-clean-url-{}", std::process::id()));
+        fs::create_dir_all(dir.join("docs"))?;
+        fs::write(dir.join("about.html"), "about")?;
+        fs::write(dir.join("docs/index.html"), "docs")?;
+        let docs = dir.join("docs");
+
+        assert_eq!(
+            Some(dir.join("about.html")),
+            clean_url(&dir.join("about"), None)
+        );
+        assert_eq!(None, clean_url(&dir.join("missing"), None));
+        // Directories are redirected to a trailing slash rather than having
+        // their index file served at a path relative links don't resolve
+        // against.
+        assert_eq!(None, clean_url(&docs, fs::metadata(&docs).ok().as_ref()));
+        assert_eq!(
+            Directory::Redirect,
+            directory(&docs, false, &["index.html".to_owned()], false)
+        );
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_header() {
+        let request = Request::from_input(
+            b"GET / HTTP/1.1\r\nHost: localhost\r\nIf-None-Match:  \"a\" \r\n\r\nX: y",
+        )
+        .unwrap();
+
+        assert_eq!(Some(b"localhost".as_ref()), request.header("host"));
+        assert_eq!(Some(b"\"a\"".as_ref()), request.header("If-None-Match"));
+        assert!(request.header("X").is_none());
+    }
 }
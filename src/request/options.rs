@@ -0,0 +1,23 @@
+use super::{Request, RequestedMethod};
+use crate::{
+    env::Environment,
+    response::{Response, Status},
+};
+
+/// Respond to an OPTIONS request, which is a CORS preflight request when it
+/// has an `Origin` and `Access-Control-Request-Method` and CORS is enabled.
+pub fn options(request: &Request<'_>, env: &Environment) -> Response<'static> {
+    if let (Some(cors), Some(origin), Some(method)) = (
+        env.cors_ref(),
+        request.header("Origin"),
+        request.header("Access-Control-Request-Method"),
+    ) {
+        return cors.preflight(
+            origin,
+            method,
+            request.header("Access-Control-Request-Headers"),
+        );
+    }
+
+    Response::new(Status::NO_CONTENT).header("Allow", RequestedMethod::ALLOWED)
+}
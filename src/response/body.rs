@@ -0,0 +1,185 @@
+use super::{write_all_vectored, Headers};
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+use crate::mmap::Mmap;
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{Error as IoError, Write},
+    rc::Rc,
+};
+
+/// Length above which files are mapped into memory rather than read.
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+const MAP_MIN_LEN: u64 = 1 << 20;
+
+/// Producer of content of an unknown length, writing it as it's produced and
+/// returning trailer fields to send after it.
+pub type Stream<'a> = Box<dyn FnOnce(&mut dyn Write) -> Result<Headers, IoError> + 'a>;
+
+/// Content of a response.
+pub enum Body<'a> {
+    /// Content in memory.
+    Bytes(Cow<'a, [u8]>),
+    /// No content.
+    Empty,
+    /// Content of a file, copied to the writer as it's read.
+    File {
+        /// File positioned at the start of the content.
+        file: File,
+        /// Length of the content.
+        len: u64,
+    },
+    /// Content of a file mapped into memory, sent without copying it.
+    #[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+    Mapped {
+        /// File the content is mapped from, to check it hasn't been
+        /// truncated before the content is read.
+        file: File,
+        /// Mapping of the content.
+        map: Mmap,
+    },
+    /// Content in memory shared with the file cache, sent without copying
+    /// it.
+    Shared(Rc<[u8]>),
+    /// Content produced while the response is written, sent with the chunked
+    /// transfer coding.
+    Stream(Stream<'a>),
+}
+
+impl Body<'_> {
+    /// Content of the start of a file, which is mapped into memory if it's
+    /// large and the `mmap` feature is enabled.
+    ///
+    /// Files that can't be mapped, including those already shorter than the
+    /// length, are copied to the writer as they're read.
+    // This could only be const without the `mmap` feature.
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn file(file: File, len: u64) -> Self {
+        #[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+        if len >= MAP_MIN_LEN {
+            if let Ok(map) = Mmap::new(&file, len) {
+                return Self::Mapped { file, map };
+            }
+        }
+
+        Self::File { file, len }
+    }
+
+    /// Length of the content, if known up front.
+    pub fn len(&self) -> Option<u64> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes.len() as u64),
+            Self::Empty => Some(0),
+            Self::File { len, .. } => Some(*len),
+            #[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+            Self::Mapped { map, .. } => Some(map.len() as u64),
+            Self::Shared(bytes) => Some(bytes.len() as u64),
+            Self::Stream(_) => None,
+        }
+    }
+}
+
+impl Debug for Body<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Bytes(bytes) => f.debug_tuple("Bytes").field(bytes).finish(),
+            Self::Empty => f.write_str("Empty"),
+            Self::File { file, len } => f
+                .debug_struct("File")
+                .field("file", file)
+                .field("len", len)
+                .finish(),
+            #[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+            Self::Mapped { file, map } => f
+                .debug_struct("Mapped")
+                .field("file", file)
+                .field("map", map)
+                .finish(),
+            Self::Shared(bytes) => f.debug_tuple("Shared").field(bytes).finish(),
+            Self::Stream(_) => f.write_str("Stream"),
+        }
+    }
+}
+
+/// Writer sending the data written to it as content with the chunked
+/// transfer coding.
+///
+/// Data is buffered and sent in chunks of around 8 KiB, or when flushed, each
+/// in a single write along with its framing.
+#[derive(Debug)]
+pub struct Chunked<W: Write> {
+    buf: Vec<u8>,
+    head: Vec<u8>,
+    inner: W,
+}
+
+impl<W: Write> Chunked<W> {
+    const CHUNK_SIZE: usize = 8192;
+
+    /// Create a writer sending the head of the response before the first
+    /// chunk.
+    pub const fn new(inner: W, head: Vec<u8>) -> Self {
+        Self {
+            buf: Vec::new(),
+            head,
+            inner,
+        }
+    }
+
+    /// Send the remaining data followed by the last chunk and trailer fields.
+    pub fn finish(mut self, trailers: &Headers) -> Result<(), IoError> {
+        let mut end = b"0\r\n".to_vec();
+
+        for (name, value) in trailers.iter() {
+            end.extend_from_slice(name.as_bytes());
+            end.extend_from_slice(b": ");
+            end.extend_from_slice(value.as_bytes());
+            end.extend_from_slice(b"\r\n");
+        }
+
+        end.extend_from_slice(b"\r\n");
+        self.write_chunk(&end)?;
+
+        self.inner.flush()
+    }
+
+    /// Send any pending head and the buffered data as a chunk, followed by
+    /// more framing.
+    fn write_chunk(&mut self, tail: &[u8]) -> Result<(), IoError> {
+        // An empty chunk would mark the end of the content.
+        let size = if self.buf.is_empty() {
+            String::new()
+        } else {
+            format!("{:x}\r\n", self.buf.len())
+        };
+        let end: &[u8] = if self.buf.is_empty() { b"" } else { b"\r\n" };
+
+        write_all_vectored(
+            &mut self.inner,
+            &[&self.head, size.as_bytes(), &self.buf, end, tail],
+        )?;
+        self.head.clear();
+        self.buf.clear();
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Chunked<W> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, IoError> {
+        self.buf.extend_from_slice(data);
+
+        if self.buf.len() >= Self::CHUNK_SIZE {
+            self.write_chunk(&[])?;
+        }
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<(), IoError> {
+        self.write_chunk(&[])?;
+
+        self.inner.flush()
+    }
+}
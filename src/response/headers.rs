@@ -0,0 +1,85 @@
+use std::borrow::Cow;
+
+/// Ordered collection of header fields, where a name may have multiple
+/// values.
+///
+/// Names are compared case-insensitively.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Headers {
+    fields: Vec<(Cow<'static, str>, String)>,
+}
+
+impl Headers {
+    /// Add a value for a name after any existing fields.
+    pub fn append(&mut self, name: impl Into<Cow<'static, str>>, value: impl Into<String>) {
+        self.fields.push((name.into(), value.into()));
+    }
+
+    /// Add all of the fields of another collection after the existing
+    /// fields.
+    pub fn append_all(&mut self, other: Self) {
+        self.fields.extend(other.fields);
+    }
+
+    /// Whether there's a field with a name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Retrieve the value of the first field with a name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// Iterate over the names and values of the fields, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_ref(), value.as_str()))
+    }
+
+    /// Remove all fields with a name.
+    pub fn remove(&mut self, name: &str) {
+        self.fields
+            .retain(|(field, _)| !field.eq_ignore_ascii_case(name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Headers;
+
+    #[test]
+    fn test_headers() {
+        let mut headers = Headers::default();
+        headers.append("Vary", "Accept-Encoding");
+        headers.append("Link", "</a.css>; rel=preload; as=style");
+        headers.append("link", "</b.js>; rel=preload; as=script".to_owned());
+
+        assert!(headers.contains("VARY"));
+        assert_eq!(Some("</a.css>; rel=preload; as=style"), headers.get("Link"));
+        assert_eq!(
+            2,
+            headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("link"))
+                .count()
+        );
+
+        headers.remove("LINK");
+        assert!(!headers.contains("Link"));
+
+        let mut trailers = Headers::default();
+        trailers.append("Server-Timing", "index;dur=1");
+        headers.append_all(trailers);
+        assert_eq!(
+            vec![
+                ("Vary", "Accept-Encoding"),
+                ("Server-Timing", "index;dur=1")
+            ],
+            headers.iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,711 @@
+mod body;
+mod headers;
+mod status;
+
+pub use self::{body::Body, headers::Headers, status::Status};
+
+use self::body::Chunked;
+use super::{charset::Charset, content_type::Mime};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    error::Error,
+    io::{Error as IoError, ErrorKind, IoSlice, Read, Write},
+    mem,
+};
+
+/// Size of the blocks file content is read in, the first of which is sent
+/// along with the head of the response.
+const FILE_BLOCK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub enum WriteError {
+    /// Failed to write data to an IO writer.
+    Io {
+        /// Reason for the error.
+        source: IoError,
+    },
+}
+
+impl Display for WriteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Io { .. } => f.write_str("failed to write data to writer"),
+        }
+    }
+}
+
+impl Error for WriteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source } => Some(source),
+        }
+    }
+}
+
+/// Response with a status, header fields, and content.
+#[derive(Debug)]
+pub struct Response<'a> {
+    body: Body<'a>,
+    chunked: bool,
+    head: bool,
+    headers: Headers,
+    status: Status,
+}
+
+impl<'a> Response<'a> {
+    /// Value of the `Server` header.
+    pub const SERVER: &'static str =
+        concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+    /// Create a response with a status and no content.
+    pub fn new(status: Status) -> Self {
+        Self {
+            body: Body::Empty,
+            chunked: true,
+            head: false,
+            headers: Headers::default(),
+            status,
+        }
+    }
+
+    /// Create a response redirecting to a location, which may be relative to
+    /// the request target.
+    pub fn redirect(status: Status, location: impl Into<String>) -> Self {
+        Self::new(status).header("Location", location)
+    }
+
+    /// Content of the response.
+    pub fn body(mut self, body: Body<'a>) -> Self {
+        self.body = body;
+
+        self
+    }
+
+    /// Whether content of an unknown length may be sent with the chunked
+    /// transfer coding, which only HTTP/1.1 clients understand.
+    ///
+    /// Otherwise the content is produced up front to find its length, and any
+    /// trailer fields are sent as header fields instead.
+    pub const fn chunked(mut self, chunked: bool) -> Self {
+        self.chunked = chunked;
+
+        self
+    }
+
+    /// Whether the response is to a HEAD request, omitting the content while
+    /// still describing it in the headers.
+    pub const fn head(mut self, head: bool) -> Self {
+        self.head = head;
+
+        self
+    }
+
+    /// Replace the status of the response.
+    pub const fn status_code(mut self, status: Status) -> Self {
+        self.status = status;
+
+        self
+    }
+
+    /// Add header fields sent with every response before the fields of this
+    /// response.
+    pub fn defaults(mut self, mut defaults: Headers) -> Self {
+        defaults.append_all(mem::take(&mut self.headers));
+        self.headers = defaults;
+
+        self
+    }
+
+    /// Add a header field, after any others with the same name.
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.append(name, value);
+
+        self
+    }
+
+    // Status of the response.
+    pub const fn status(&self) -> Status {
+        self.status
+    }
+
+    // Whether the response has no content.
+    pub const fn is_empty(&self) -> bool {
+        matches!(self.body, Body::Empty)
+    }
+
+    // Retrieve a mutable reference to the header fields.
+    pub fn headers_mut(&mut self) -> &mut Headers {
+        &mut self.headers
+    }
+
+    /// Write the response to a writer.
+    ///
+    /// The `Content-Type` is sniffed from the content when not provided, and
+    /// the framing headers are derived from the content.
+    pub fn write(self, buf: &mut impl Write) -> Result<(), WriteError> {
+        self.write_inner(buf)
+            .map_err(|source| WriteError::Io { source })
+    }
+
+    fn write_inner(mut self, buf: &mut impl Write) -> Result<(), IoError> {
+        let has_content = self.status.allows_content();
+
+        if has_content && !self.chunked {
+            if let Body::Stream(stream) = mem::replace(&mut self.body, Body::Empty) {
+                let mut content = Vec::new();
+                let trailers = stream(&mut content)?;
+                self.headers.remove("Trailer");
+                self.headers.append_all(trailers);
+                self.body = Body::Bytes(content.into());
+            }
+        }
+
+        // The head is assembled up front so that it goes out in the same
+        // write as the start of the content rather than a write per line.
+        let mut head = Vec::with_capacity(256);
+        head.extend_from_slice(b"HTTP/1.1 ");
+        head.extend_from_slice(self.status.code().to_string().as_bytes());
+        head.push(b' ');
+        head.extend_from_slice(self.status.reason().as_bytes());
+        head.extend_from_slice(b"\r\n");
+
+        for (name, value) in self.headers.iter() {
+            Self::header_line(&mut head, name, value)?;
+        }
+
+        if has_content {
+            if !self.headers.contains("Content-Type") {
+                let content_type = match &self.body {
+                    Body::Bytes(bytes) if !bytes.is_empty() => {
+                        let mime = Mime::from_input(bytes);
+
+                        mime.with_charset(Charset::detect(mime, bytes, false, None))
+                    }
+                    _ => Mime::OctetStream.with_charset(None),
+                };
+                Self::header_line(&mut head, "Content-Type", &content_type.to_string())?;
+            }
+
+            match self.body.len() {
+                Some(len) => Self::header_line(&mut head, "Content-Length", &len.to_string())?,
+                None => Self::header_line(&mut head, "Transfer-Encoding", "chunked")?,
+            }
+        }
+
+        head.extend_from_slice(b"\r\n");
+
+        if !has_content || self.head {
+            return buf.write_all(&head);
+        }
+
+        match self.body {
+            Body::Bytes(bytes) => write_all_vectored(buf, &[&head, &bytes]),
+            Body::Shared(bytes) => write_all_vectored(buf, &[&head, &bytes]),
+            Body::Empty => buf.write_all(&head),
+            Body::File { file, len } => {
+                let mut file = file.take(len);
+                let size =
+                    usize::try_from(len).map_or(FILE_BLOCK_SIZE, |len| len.min(FILE_BLOCK_SIZE));
+                let mut block = vec![0; size];
+                let mut pending = head.as_slice();
+
+                loop {
+                    let read = match file.read(&mut block) {
+                        Ok(0) => break,
+                        Ok(read) => read,
+                        Err(source) if source.kind() == ErrorKind::Interrupted => continue,
+                        Err(source) => return Err(source),
+                    };
+
+                    write_all_vectored(buf, &[pending, &block[..read]])?;
+                    pending = &[];
+                }
+
+                // The head still goes out if the file turned out to be empty.
+                buf.write_all(pending)
+            }
+            #[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+            Body::Mapped { file, map } => {
+                let mut pending = head.as_slice();
+                let mut end = 0;
+
+                // Reading a mapping past the end of a file that's been
+                // truncated faults, so the file is checked to still cover
+                // each block before it's read, and the response is cut short
+                // otherwise.
+                for block in map.chunks(FILE_BLOCK_SIZE) {
+                    end += block.len() as u64;
+
+                    if file.metadata()?.len() < end {
+                        return Err(IoError::new(
+                            ErrorKind::UnexpectedEof,
+                            "file was truncated while being sent",
+                        ));
+                    }
+
+                    write_all_vectored(buf, &[pending, block])?;
+                    pending = &[];
+                }
+
+                Ok(())
+            }
+            Body::Stream(stream) => {
+                let mut chunked = Chunked::new(buf, head);
+                let trailers = stream(&mut chunked)?;
+
+                chunked.finish(&trailers)
+            }
+        }
+    }
+
+    fn header_line(buf: &mut impl Write, name: &str, value: &str) -> Result<(), IoError> {
+        buf.write_all(name.as_bytes())?;
+        buf.write_all(b": ")?;
+        buf.write_all(value.as_bytes())?;
+
+        buf.write_all(b"\r\n")
+    }
+}
+
+/// Write all of a sequence of slices to a writer, with as few writes as the
+/// writer allows.
+pub fn write_all_vectored<W: Write + ?Sized>(buf: &mut W, slices: &[&[u8]]) -> Result<(), IoError> {
+    let mut slices = slices
+        .iter()
+        .copied()
+        .filter(|slice| !slice.is_empty())
+        .collect::<Vec<_>>();
+    let mut start = 0;
+
+    while start < slices.len() {
+        let io_slices = slices[start..]
+            .iter()
+            .map(|slice| IoSlice::new(slice))
+            .collect::<Vec<_>>();
+        let mut written = match buf.write_vectored(&io_slices) {
+            Ok(0) => return Err(IoError::from(ErrorKind::WriteZero)),
+            Ok(written) => written,
+            Err(source) if source.kind() == ErrorKind::Interrupted => continue,
+            Err(source) => return Err(source),
+        };
+
+        // Skip the slices written in full and the written part of the next.
+        while start < slices.len() && written >= slices[start].len() {
+            written -= slices[start].len();
+            start += 1;
+        }
+
+        if let Some(slice) = slices.get_mut(start) {
+            *slice = &slice[written..];
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_all_vectored, Body, Headers, Response, Status};
+    use crate::{content_type::Extension, hints};
+    use std::{
+        env,
+        error::Error,
+        fs::{self, File},
+        io::{self, Error as IoError, IoSlice, Write},
+        net::{TcpListener, TcpStream},
+        thread,
+        time::Instant,
+    };
+
+    /// Writer counting the writes made to it, each standing in for a system
+    /// call on a socket, and accepting at most a limited number of bytes per
+    /// write.
+    #[derive(Default)]
+    struct CountingWriter {
+        buf: Vec<u8>,
+        limit: Option<usize>,
+        writes: usize,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, data: &[u8]) -> Result<usize, IoError> {
+            self.write_vectored(&[IoSlice::new(data)])
+        }
+
+        fn write_vectored(&mut self, slices: &[IoSlice<'_>]) -> Result<usize, IoError> {
+            self.writes += 1;
+            let mut written = 0;
+
+            for slice in slices {
+                let len = self
+                    .limit
+                    .map_or(slice.len(), |limit| slice.len().min(limit - written));
+                self.buf.extend_from_slice(&slice[..len]);
+                written += len;
+            }
+
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> Result<(), IoError> {
+            Ok(())
+        }
+    }
+
+    /// Writer passing writes on to another, counting them.
+    struct Counted<W> {
+        inner: W,
+        writes: usize,
+    }
+
+    impl<W: Write> Write for Counted<W> {
+        fn write(&mut self, data: &[u8]) -> Result<usize, IoError> {
+            self.writes += 1;
+            self.inner.write(data)
+        }
+
+        fn write_vectored(&mut self, slices: &[IoSlice<'_>]) -> Result<usize, IoError> {
+            self.writes += 1;
+            self.inner.write_vectored(slices)
+        }
+
+        fn flush(&mut self) -> Result<(), IoError> {
+            self.inner.flush()
+        }
+    }
+
+    /// Write a response the way responses used to be written, with a write
+    /// for each fragment of each line of the head and one for the content.
+    fn write_fragmented(
+        buf: &mut impl Write,
+        response: Response<'_>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut bytes = Vec::new();
+        response.write(&mut bytes)?;
+        let head_len = bytes
+            .windows(4)
+            .position(|x| x == b"\r\n\r\n")
+            .map_or(bytes.len(), |idx| idx + 2);
+
+        for line in bytes[..head_len].split_inclusive(|x| *x == b'\n') {
+            let line = &line[..line.len() - 2];
+            let mut fragments = line.splitn(2, |x| *x == b':');
+            buf.write_all(fragments.next().unwrap_or_default())?;
+
+            if let Some(value) = fragments.next() {
+                buf.write_all(b":")?;
+                buf.write_all(value)?;
+            }
+
+            buf.write_all(b"\r\n")?;
+        }
+
+        buf.write_all(b"\r\n")?;
+        buf.write_all(&bytes[head_len + 2..])?;
+
+        Ok(())
+    }
+
+    fn written(response: Response<'_>) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let mut buf = Vec::new();
+        response.write(&mut buf)?;
+
+        Ok(String::from_utf8(buf)?)
+    }
+
+    #[test]
+    fn test_write_all_vectored() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut writer = CountingWriter {
+            limit: Some(3),
+            ..CountingWriter::default()
+        };
+        write_all_vectored(&mut writer, &[b"ab", b"", b"cdef", b"g"])?;
+
+        assert_eq!(b"abcdefg".as_ref(), writer.buf);
+        assert_eq!(3, writer.writes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_count() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let response = || {
+            let mut defaults = Headers::default();
+            defaults.append("Server", Response::SERVER);
+            defaults.append("Date", "Thu, 01 Jan 1970 00:00:00 GMT");
+            defaults.append("Connection", "close");
+
+            Response::new(Status::OK)
+                .header("ETag", "\"a\"")
+                .header("Cache-Control", "no-cache")
+                .defaults(defaults)
+        };
+
+        // Writing each fragment of each line separately took 4 writes for the
+        // status line, 4 per header line, and more for the content.
+        let mut writer = CountingWriter::default();
+        response()
+            .body(Body::Bytes(b"{}".as_ref().into()))
+            .write(&mut writer)?;
+        assert_eq!(1, writer.writes);
+
+        let path = env::temp_dir().join(format!("picoserve-write-count-{}", std::process::id()));
+        let content = vec![b'a'; 100_000];
+        fs::write(&path, &content)?;
+        let mut writer = CountingWriter::default();
+        response()
+            .body(Body::File {
+                file: File::open(&path)?,
+                len: 100_000,
+            })
+            .write(&mut writer)?;
+        fs::remove_file(path)?;
+        assert_eq!(2, writer.writes);
+        assert!(writer.buf.ends_with(&content));
+
+        let mut writer = CountingWriter::default();
+        response()
+            .body(Body::Stream(Box::new(|buf| {
+                buf.write_all(b"<p>")?;
+                buf.write_all(b"listing")?;
+
+                Ok(Headers::default())
+            })))
+            .write(&mut writer)?;
+        assert_eq!(1, writer.writes);
+        assert!(String::from_utf8(writer.buf)?.ends_with("\r\n\r\na\r\n<p>listing\r\n0\r\n\r\n"));
+
+        Ok(())
+    }
+
+    /// Microbenchmark of writing responses to a loopback socket a fragment at
+    /// a time, as they used to be, and with the head and content in a single
+    /// vectored write, printing the writes and time taken by each.
+    ///
+    /// Run with `cargo test --release bench_write -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_write() -> Result<(), Box<dyn Error + Send + Sync>> {
+        const RESPONSES: u32 = 10_000;
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let reader = thread::spawn(move || -> Result<u64, IoError> {
+            let (mut stream, _) = listener.accept()?;
+
+            io::copy(&mut stream, &mut io::sink())
+        });
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let mut stream = Counted {
+            inner: stream,
+            writes: 0,
+        };
+        let response = || {
+            Response::new(Status::OK)
+                .header("Date", "Thu, 01 Jan 1970 00:00:00 GMT")
+                .header("Connection", "close")
+                .header("ETag", "\"a\"")
+                .header("Cache-Control", "no-cache")
+                .header("Content-Type", "application/json")
+                .body(Body::Bytes(b"{\"ok\":true}".as_ref().into()))
+        };
+
+        let start = Instant::now();
+
+        for _ in 0..RESPONSES {
+            write_fragmented(&mut stream, response())?;
+        }
+
+        let (fragmented_writes, fragmented_time) = (stream.writes, start.elapsed());
+        stream.writes = 0;
+        let start = Instant::now();
+
+        for _ in 0..RESPONSES {
+            response().write(&mut stream)?;
+        }
+
+        let (vectored_writes, vectored_time) = (stream.writes, start.elapsed());
+        drop(stream);
+        let _ = reader.join().map_err(|_| "reader panicked")??;
+
+        println!(
+            "fragmented: {} writes per response, {:?} per response",
+            fragmented_writes / RESPONSES as usize,
+            fragmented_time / RESPONSES
+        );
+        println!(
+            "vectored: {} writes per response, {:?} per response",
+            vectored_writes / RESPONSES as usize,
+            vectored_time / RESPONSES
+        );
+        assert_eq!(RESPONSES as usize, vectored_writes);
+        assert!(fragmented_writes > vectored_writes * 10);
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+    #[test]
+    fn test_mapped() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let path = env::temp_dir().join(format!("picoserve-mapped-{}", std::process::id()));
+        let content = vec![b'a'; 2 << 20];
+        fs::write(&path, &content)?;
+        let mapped = || -> Result<_, IoError> {
+            let body = Body::file(File::open(&path)?, content.len() as u64);
+            assert!(matches!(body, Body::Mapped { .. }));
+
+            Ok(Response::new(Status::OK).body(body))
+        };
+
+        let mut writer = CountingWriter::default();
+        mapped()?.write(&mut writer)?;
+        assert!(writer.buf.ends_with(&content));
+
+        // Files already shorter than the content aren't mapped.
+        let len = content.len() as u64 + 1;
+        assert!(matches!(
+            Body::file(File::open(&path)?, len),
+            Body::File { .. }
+        ));
+
+        // Files truncated after being mapped aren't read past their end.
+        let response = mapped()?;
+        File::create(&path)?.set_len(0)?;
+        assert!(response.write(&mut CountingWriter::default()).is_err());
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_line() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut buf = Vec::new();
+        Response::header_line(&mut buf, "Content-Type", Extension::Json.mime().name())?;
+        assert_eq!(b"Content-Type: application/json\r\n".as_ref(), buf);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ok() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut defaults = Headers::default();
+        defaults.append("Server", Response::SERVER);
+        let response = Response::new(Status::OK)
+            .header("ETag", "\"a\"")
+            .body(Body::Bytes(b"test".as_ref().into()))
+            .defaults(defaults);
+
+        assert_eq!(
+            format!(
+                "HTTP/1.1 200 OK\r\nServer: {}\r\nETag: \"a\"\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 4\r\n\r\ntest",
+                Response::SERVER
+            ),
+            written(response)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_status() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let response = Response::new(Status::METHOD_NOT_ALLOWED).header("Allow", "GET, HEAD");
+
+        assert_eq!(
+            "HTTP/1.1 405 Method Not Allowed\r\nAllow: GET, HEAD\r\nContent-Type: application/octet-stream\r\nContent-Length: 0\r\n\r\n",
+            written(response)?
+        );
+        assert!(written(Response::new(Status::new(599)))?.starts_with("HTTP/1.1 599 \r\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_redirect() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let response = Response::redirect(Status::MOVED_PERMANENTLY, "/docs/?v=1");
+
+        assert_eq!(
+            "HTTP/1.1 301 Moved Permanently\r\nLocation: /docs/?v=1\r\nContent-Type: application/octet-stream\r\nContent-Length: 0\r\n\r\n",
+            written(response)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_early_hints() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let links =
+            hints::extract(br#"<link rel="stylesheet" href="/a.css"><script src="b.js"></script>"#);
+        let mut response = Response::new(Status::EARLY_HINTS);
+
+        for link in &links {
+            response.headers_mut().append("Link", link.to_string());
+        }
+
+        assert_eq!(
+            "HTTP/1.1 103 Early Hints\r\nLink: </a.css>; rel=preload; as=style\r\nLink: <b.js>; rel=preload; as=script\r\n\r\n",
+            written(response)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_modified() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let response = Response::new(Status::NOT_MODIFIED)
+            .header("ETag", "\"a\"")
+            .body(Body::Bytes(b"test".as_ref().into()));
+
+        assert_eq!(
+            "HTTP/1.1 304 Not Modified\r\nETag: \"a\"\r\n\r\n",
+            written(response)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_head() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let response = Response::new(Status::OK)
+            .body(Body::Bytes(b"test".as_ref().into()))
+            .head(true);
+
+        assert!(written(response)?.ends_with("Content-Length: 4\r\n\r\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let stream = || {
+            Body::Stream(Box::new(|buf| {
+                buf.write_all(b"<h2>")?;
+                buf.flush()?;
+                buf.write_all(b"files</h2>")?;
+
+                let mut trailers = Headers::default();
+                trailers.append("Server-Timing", "index;dur=1");
+
+                Ok(trailers)
+            }))
+        };
+        let response = |chunked| {
+            Response::new(Status::OK)
+                .header("Content-Type", Extension::Html.mime().name())
+                .header("Trailer", "Server-Timing")
+                .body(stream())
+                .chunked(chunked)
+        };
+
+        assert_eq!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nTrailer: Server-Timing\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n<h2>\r\na\r\nfiles</h2>\r\n0\r\nServer-Timing: index;dur=1\r\n\r\n",
+            written(response(true))?
+        );
+        assert_eq!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nServer-Timing: index;dur=1\r\nContent-Length: 14\r\n\r\n<h2>files</h2>",
+            written(response(false))?
+        );
+
+        Ok(())
+    }
+}
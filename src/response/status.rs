@@ -0,0 +1,157 @@
+/// Status code of a response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Status(u16);
+
+impl Status {
+    pub const EARLY_HINTS: Self = Self::new(103);
+    pub const OK: Self = Self::new(200);
+    pub const NO_CONTENT: Self = Self::new(204);
+    pub const MOVED_PERMANENTLY: Self = Self::new(301);
+    pub const NOT_MODIFIED: Self = Self::new(304);
+    pub const BAD_REQUEST: Self = Self::new(400);
+    pub const FORBIDDEN: Self = Self::new(403);
+    pub const NOT_FOUND: Self = Self::new(404);
+    pub const METHOD_NOT_ALLOWED: Self = Self::new(405);
+    pub const NOT_ACCEPTABLE: Self = Self::new(406);
+    pub const PRECONDITION_FAILED: Self = Self::new(412);
+    pub const INTERNAL_SERVER_ERROR: Self = Self::new(500);
+
+    /// Create a status from a three digit code.
+    pub const fn new(code: u16) -> Self {
+        Self(code)
+    }
+
+    pub const fn code(self) -> u16 {
+        self.0
+    }
+
+    /// Canonical reason phrase of the status, which is empty for codes that
+    /// aren't registered.
+    pub const fn reason(self) -> &'static str {
+        match self.0 {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            102 => "Processing",
+            103 => "Early Hints",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            203 => "Non-Authoritative Information",
+            204 => "No Content",
+            205 => "Reset Content",
+            206 => "Partial Content",
+            207 => "Multi-Status",
+            208 => "Already Reported",
+            226 => "IM Used",
+            300 => "Multiple Choices",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            305 => "Use Proxy",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            402 => "Payment Required",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            406 => "Not Acceptable",
+            407 => "Proxy Authentication Required",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            410 => "Gone",
+            411 => "Length Required",
+            412 => "Precondition Failed",
+            413 => "Content Too Large",
+            414 => "URI Too Long",
+            415 => "Unsupported Media Type",
+            416 => "Range Not Satisfiable",
+            417 => "Expectation Failed",
+            421 => "Misdirected Request",
+            422 => "Unprocessable Content",
+            423 => "Locked",
+            424 => "Failed Dependency",
+            425 => "Too Early",
+            426 => "Upgrade Required",
+            428 => "Precondition Required",
+            429 => "Too Many Requests",
+            431 => "Request Header Fields Too Large",
+            451 => "Unavailable For Legal Reasons",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            505 => "HTTP Version Not Supported",
+            506 => "Variant Also Negotiates",
+            507 => "Insufficient Storage",
+            508 => "Loop Detected",
+            511 => "Network Authentication Required",
+            _ => "",
+        }
+    }
+
+    /// Whether the status is an interim response without content.
+    pub const fn is_informational(self) -> bool {
+        self.0 >= 100 && self.0 < 200
+    }
+
+    /// Whether the status redirects to another location.
+    pub const fn is_redirection(self) -> bool {
+        self.0 >= 300 && self.0 < 400
+    }
+
+    /// Whether the status is a client or server error.
+    pub const fn is_error(self) -> bool {
+        self.0 >= 400 && self.0 < 600
+    }
+
+    /// Whether a response with the status may have content, which isn't the
+    /// case for interim, `204 No Content`, and `304 Not Modified` responses.
+    pub const fn allows_content(self) -> bool {
+        !self.is_informational() && self.0 != 204 && self.0 != 304
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn test_reason() {
+        assert_eq!("Early Hints", Status::EARLY_HINTS.reason());
+        assert_eq!("Not Modified", Status::NOT_MODIFIED.reason());
+        assert_eq!(
+            "Internal Server Error",
+            Status::INTERNAL_SERVER_ERROR.reason()
+        );
+        assert_eq!("Permanent Redirect", Status::new(308).reason());
+        assert_eq!("", Status::new(599).reason());
+    }
+
+    #[test]
+    fn test_allows_content() {
+        assert!(Status::OK.allows_content());
+        assert!(Status::NOT_FOUND.allows_content());
+        assert!(!Status::EARLY_HINTS.allows_content());
+        assert!(!Status::new(204).allows_content());
+        assert!(!Status::NOT_MODIFIED.allows_content());
+    }
+
+    #[test]
+    fn test_is_error() {
+        assert!(Status::NOT_FOUND.is_error());
+        assert!(Status::INTERNAL_SERVER_ERROR.is_error());
+        assert!(!Status::NOT_MODIFIED.is_error());
+        assert!(!Status::new(600).is_error());
+    }
+
+    #[test]
+    fn test_is_redirection() {
+        assert!(Status::MOVED_PERMANENTLY.is_redirection());
+        assert!(Status::new(308).is_redirection());
+        assert!(!Status::NOT_FOUND.is_redirection());
+    }
+}
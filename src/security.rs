@@ -0,0 +1,179 @@
+use super::response::Headers;
+
+/// Header fields managed by the security presets, in the order they're sent.
+const NAMES: &[&str] = &[
+    "Content-Security-Policy",
+    "X-Frame-Options",
+    "Referrer-Policy",
+    "Permissions-Policy",
+    "X-Content-Type-Options",
+    "Strict-Transport-Security",
+];
+
+/// Set of security header fields sent with every response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Preset {
+    /// Only disable content type sniffing.
+    Off,
+    /// Policies that keep most sites working, such as allowing inline scripts
+    /// and resources from any secure origin.
+    Relaxed,
+    /// Policies for sites that only load their own resources and are never
+    /// framed.
+    Strict,
+}
+
+impl Preset {
+    pub fn new(preset: &str) -> Option<Self> {
+        Some(match preset {
+            "off" => Self::Off,
+            "relaxed" => Self::Relaxed,
+            "strict" => Self::Strict,
+            _ => return None,
+        })
+    }
+
+    /// Names and values of the fields of the preset.
+    const fn fields(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Off => &[("X-Content-Type-Options", "nosniff")],
+            Self::Relaxed => &[
+                (
+                    "Content-Security-Policy",
+                    "default-src 'self' https: data: blob: 'unsafe-inline' 'unsafe-eval'; \
+                     frame-ancestors 'self'",
+                ),
+                ("X-Frame-Options", "SAMEORIGIN"),
+                ("Referrer-Policy", "strict-origin-when-cross-origin"),
+                (
+                    "Permissions-Policy",
+                    "camera=(), geolocation=(), microphone=()",
+                ),
+                ("X-Content-Type-Options", "nosniff"),
+            ],
+            Self::Strict => &[
+                (
+                    "Content-Security-Policy",
+                    "default-src 'self'; base-uri 'self'; form-action 'self'; \
+                     frame-ancestors 'none'; object-src 'none'",
+                ),
+                ("X-Frame-Options", "DENY"),
+                ("Referrer-Policy", "no-referrer"),
+                (
+                    "Permissions-Policy",
+                    "camera=(), geolocation=(), microphone=(), payment=(), usb=()",
+                ),
+                ("X-Content-Type-Options", "nosniff"),
+            ],
+        }
+    }
+}
+
+/// Replacement value for one of the fields of a preset, given as
+/// `Name: value`, where an empty value removes the field.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Override {
+    name: &'static str,
+    value: Option<String>,
+}
+
+impl Override {
+    /// Parse an override in the form `Name: value`, such as
+    /// `X-Frame-Options: SAMEORIGIN`, for a field managed by the presets.
+    pub fn new(field: &str) -> Option<Self> {
+        let (name, value) = field.split_once(':')?;
+        let name = NAMES
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(name.trim()))?;
+        let value = value.trim();
+
+        if !value.bytes().all(|x| x == b' ' || x.is_ascii_graphic()) {
+            return None;
+        }
+
+        Some(Self {
+            name,
+            value: Some(value)
+                .filter(|value| !value.is_empty())
+                .map(From::from),
+        })
+    }
+}
+
+/// Build the security fields of a preset, replaced by any overrides and
+/// followed by a `Strict-Transport-Security` field if given a max age.
+pub fn headers(preset: Preset, overrides: &[Override], hsts: Option<u64>) -> Headers {
+    let hsts = hsts.map(|max_age| format!("max-age={max_age}; includeSubDomains"));
+    let preset = preset
+        .fields()
+        .iter()
+        .map(|(name, value)| (*name, Some((*value).to_owned())))
+        .chain(hsts.map(|value| ("Strict-Transport-Security", Some(value))));
+    let mut headers = Headers::default();
+
+    for name in NAMES {
+        // The last override for a field wins.
+        let value = overrides
+            .iter()
+            .rev()
+            .find(|field| field.name == *name)
+            .map(|field| field.value.clone())
+            .or_else(|| {
+                preset
+                    .clone()
+                    .find(|(field, _)| field == name)
+                    .map(|(_, value)| value)
+            })
+            .flatten();
+
+        if let Some(value) = value {
+            headers.append(*name, value);
+        }
+    }
+
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{headers, Override, Preset};
+
+    #[test]
+    fn test_override() {
+        assert_eq!(
+            Some(Override {
+                name: "X-Frame-Options",
+                value: Some("SAMEORIGIN".to_owned()),
+            }),
+            Override::new("x-frame-options: SAMEORIGIN")
+        );
+        assert_eq!(
+            Some(None),
+            Override::new("Content-Security-Policy:").map(|field| field.value)
+        );
+        assert!(Override::new("X-Custom: value").is_none());
+        assert!(Override::new("X-Frame-Options").is_none());
+    }
+
+    #[test]
+    fn test_headers() {
+        assert_eq!(
+            vec![("X-Content-Type-Options", "nosniff")],
+            headers(Preset::Off, &[], None).iter().collect::<Vec<_>>()
+        );
+
+        let overrides = [
+            Override::new("Content-Security-Policy:").unwrap(),
+            Override::new("Referrer-Policy: same-origin").unwrap(),
+        ];
+        let strict = headers(Preset::Strict, &overrides, Some(31_536_000));
+        assert!(!strict.contains("Content-Security-Policy"));
+        assert_eq!(Some("DENY"), strict.get("X-Frame-Options"));
+        assert_eq!(Some("same-origin"), strict.get("Referrer-Policy"));
+        assert_eq!(
+            Some("max-age=31536000; includeSubDomains"),
+            strict.get("Strict-Transport-Security")
+        );
+        assert_eq!(5, strict.iter().count());
+    }
+}
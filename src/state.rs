@@ -0,0 +1,44 @@
+use super::{
+    date::Cache as DateCache, file_cache::FileCache, headers_file::HeadersFile,
+    hints::Cache as HintCache,
+};
+
+/// Mutable state kept across requests.
+#[derive(Debug)]
+pub struct State {
+    date: DateCache,
+    file_cache: FileCache,
+    headers_file: HeadersFile,
+    hints: HintCache,
+}
+
+impl State {
+    pub fn new(headers_file: HeadersFile, file_cache: FileCache) -> Self {
+        Self {
+            date: DateCache::default(),
+            file_cache,
+            headers_file,
+            hints: HintCache::default(),
+        }
+    }
+
+    // Retrieve a mutable reference to the cached value of the `Date` header.
+    pub fn date_mut(&mut self) -> &mut DateCache {
+        &mut self.date
+    }
+
+    // Retrieve a mutable reference to the in-memory cache of files.
+    pub fn file_cache_mut(&mut self) -> &mut FileCache {
+        &mut self.file_cache
+    }
+
+    // Retrieve a mutable reference to the rules of the `_headers` file.
+    pub fn headers_file_mut(&mut self) -> &mut HeadersFile {
+        &mut self.headers_file
+    }
+
+    // Retrieve a mutable reference to the cache of HTML preload hints.
+    pub fn hints_mut(&mut self) -> &mut HintCache {
+        &mut self.hints
+    }
+}